@@ -0,0 +1,163 @@
+use std::{fmt::Display, str::FromStr};
+
+use eyre::Report;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ant {
+    /// Position on the rod in the range [0, 1)
+    pub position: f32,
+    /// The speed of the ant, negative if it walks left
+    pub speed: f32,
+    #[serde(rename = "type")]
+    pub typ: AntType,
+    /// Identifies the ant, stays the same even after collisions
+    pub id: usize,
+}
+
+impl Ant {
+    /// Creates ant with random position and direction
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self::random_facing(rng, 0.5)
+    }
+
+    /// Creates ant with random position that faces right with the
+    /// probability `right`
+    pub fn random_facing(rng: &mut impl Rng, right: f64) -> Self {
+        Self {
+            position: rng.gen_range(0.0..1.),
+            speed: if rng.gen_bool(right) { 1. } else { -1. },
+            typ: AntType::Some,
+            id: 0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Default, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AntType {
+    #[default]
+    None,
+    #[serde(rename = "ant")]
+    Some,
+    Molly,
+    /// Ant followed by the user, the number distinguishes the tracked ants
+    Tracked(usize),
+}
+
+/// The end of the rod
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Side::Left => f.write_str("left"),
+            Side::Right => f.write_str("right"),
+        }
+    }
+}
+
+/// Records when and where an ant fell from the rod
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Fall {
+    pub id: usize,
+    #[serde(rename = "type")]
+    pub typ: AntType,
+    pub side: Side,
+    pub time: f32,
+}
+
+/// Something that happened in the simulation
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// Two ants met, `left` is the ant that came from the left
+    Collision {
+        left: usize,
+        right: usize,
+        time: f32,
+        position: f32,
+    },
+    /// An ant fell from the rod
+    Fall(Fall),
+    /// Molly changed the direction in which she walks
+    MollyTurned { time: f32, position: f32 },
+}
+
+/// What happens when two ants meet
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Collision {
+    /// Both ants turn around and keep their speeds
+    #[default]
+    Reverse,
+    /// The ants exchange their velocities as in elastic collision of equal
+    /// masses
+    Exchange,
+}
+
+impl Collision {
+    /// Applies the collision to two ants that touch, `a` is on the left
+    pub fn apply(self, a: &mut Ant, b: &mut Ant) {
+        (a.speed, b.speed) = self.speeds(a.speed, b.speed);
+    }
+
+    /// Gets the speeds of two ants with the speeds `a` and `b` after they
+    /// collide
+    pub fn speeds(self, a: f32, b: f32) -> (f32, f32) {
+        match self {
+            Collision::Reverse => (-a, -b),
+            Collision::Exchange => (b, a),
+        }
+    }
+}
+
+impl FromStr for Collision {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reverse" => Ok(Collision::Reverse),
+            "exchange" => Ok(Collision::Exchange),
+            _ => Err(Report::msg(format!("invalid collision model {s}"))),
+        }
+    }
+}
+
+/// What happens when an ant reaches an end of the rod
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Boundary {
+    /// The ant falls from the rod
+    #[default]
+    Absorbing,
+    /// The ant turns around as if it hit a wall
+    Reflecting,
+    /// The ant waits at the end for the given time and then turns around
+    Sticky(f32),
+    /// The ant continues from the other end, both ends must be periodic
+    Periodic,
+}
+
+impl FromStr for Boundary {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "absorbing" => Ok(Boundary::Absorbing),
+            None if s == "reflecting" => Ok(Boundary::Reflecting),
+            None if s == "periodic" => Ok(Boundary::Periodic),
+            Some(("sticky", t)) => match t.parse() {
+                Ok(t) if t >= 0. => Ok(Boundary::Sticky(t)),
+                _ => Err(Report::msg(format!("invalid sticky time {t}"))),
+            },
+            _ => Err(Report::msg(format!("invalid boundary {s}"))),
+        }
+    }
+}
@@ -0,0 +1,407 @@
+//! Two dimensional variant of the problem. The ants walk in the arena, when
+//! two ants meet head-on they both turn around and when an ant walks over
+//! the edge of the arena it falls.
+
+use std::{f32::consts::TAU, str::FromStr};
+
+use eyre::{Report, Result};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    builder::{common_setters, Common},
+    AntType,
+};
+
+/// Cosine of the largest angle at which the ants still meet head-on
+const HEAD_ON: f32 = 0.95;
+
+/// Shape of the arena, it is inside the square [0, 1)²
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Shape {
+    #[default]
+    Square,
+    /// Disc in the center of the square
+    Disc,
+}
+
+impl Shape {
+    /// Checks whether the point is inside the arena
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        match self {
+            Shape::Square => (0. ..1.).contains(&x) && (0. ..1.).contains(&y),
+            Shape::Disc => {
+                let (dx, dy) = (x - 0.5, y - 0.5);
+                dx * dx + dy * dy < 0.25
+            }
+        }
+    }
+}
+
+impl FromStr for Shape {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "square" => Ok(Shape::Square),
+            "disc" => Ok(Shape::Disc),
+            _ => Err(Report::msg(format!("invalid arena shape {s}"))),
+        }
+    }
+}
+
+/// The directions in which the ants walk
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Directions {
+    /// Only up, down, left or right
+    #[default]
+    Cardinal,
+    /// Any direction
+    Any,
+}
+
+impl FromStr for Directions {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cardinal" => Ok(Directions::Cardinal),
+            "any" => Ok(Directions::Any),
+            _ => Err(Report::msg(format!("invalid directions {s}"))),
+        }
+    }
+}
+
+/// Ant in the arena
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArenaAnt {
+    pub x: f32,
+    pub y: f32,
+    /// The velocity of the ant
+    pub vx: f32,
+    pub vy: f32,
+    pub typ: AntType,
+    /// Identifies the ant, stays the same even after collisions
+    pub id: usize,
+}
+
+/// Records when and where an ant fell from the arena
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Exit {
+    pub id: usize,
+    pub typ: AntType,
+    pub time: f32,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// The simulation of ants in the arena, the ants move by a fixed step
+#[derive(Clone)]
+pub struct Arena {
+    ants: Vec<ArenaAnt>,
+    ant_step: f32,
+    // number of steps
+    time: usize,
+    shape: Shape,
+    // the ants closer than this meet
+    radius: f32,
+    exits: Vec<Exit>,
+    collisions: usize,
+    seed: u64,
+}
+
+/// Configures and creates [`Arena`]
+#[derive(Clone, Debug)]
+pub struct ArenaBuilder {
+    common: Common,
+    shape: Shape,
+    directions: Directions,
+    radius: f32,
+    ants: Option<Vec<ArenaAnt>>,
+}
+
+impl Arena {
+    /// Creates builder for the arena with the default parameters
+    pub fn builder() -> ArenaBuilder {
+        ArenaBuilder::default()
+    }
+
+    /// Moves all the ants by one step
+    pub fn step(&mut self) {
+        for a in &mut self.ants {
+            a.x += a.vx * self.ant_step;
+            a.y += a.vy * self.ant_step;
+        }
+
+        // ants that are close and walk against each other turn around, the
+        // ants that only cross their paths pass each other
+        let r2 = self.radius * self.radius;
+        for i in 0..self.ants.len() {
+            let (l, r) = self.ants.split_at_mut(i + 1);
+            let a = &mut l[i];
+            for b in r {
+                let (dx, dy) = (b.x - a.x, b.y - a.y);
+                let (dvx, dvy) = (b.vx - a.vx, b.vy - a.vy);
+                // the velocities are nearly opposite and the ants walk
+                // against each other along the line between them
+                let speeds = a.vx.hypot(a.vy) * b.vx.hypot(b.vy);
+                let opposing = a.vx * b.vx + a.vy * b.vy < -HEAD_ON * speeds;
+                let approach = -(dx * dvx + dy * dvy);
+                let aligned =
+                    approach > HEAD_ON * dx.hypot(dy) * dvx.hypot(dvy);
+                if dx * dx + dy * dy < r2 && opposing && aligned {
+                    (a.vx, a.vy) = (-a.vx, -a.vy);
+                    (b.vx, b.vy) = (-b.vx, -b.vy);
+                    self.collisions += 1;
+                }
+            }
+        }
+
+        self.time += 1;
+        let time = self.time();
+
+        // remove those that have fallen
+        let shape = self.shape;
+        let exits = &mut self.exits;
+        self.ants.retain(|a| {
+            if shape.contains(a.x, a.y) {
+                return true;
+            }
+            exits.push(Exit {
+                id: a.id,
+                typ: a.typ,
+                time,
+                x: a.x,
+                y: a.y,
+            });
+            false
+        });
+    }
+
+    pub fn has_ants(&self) -> bool {
+        !self.ants.is_empty()
+    }
+
+    /// Gets the ants in the arena
+    pub fn ants(&self) -> &[ArenaAnt] {
+        &self.ants
+    }
+
+    /// Gets the ants that have already fallen, in the order in which they
+    /// fell
+    pub fn exits(&self) -> &[Exit] {
+        &self.exits
+    }
+
+    /// Gets the fall of molly if she has already fallen
+    pub fn molly_exit(&self) -> Option<&Exit> {
+        self.exits.iter().find(|e| e.typ == AntType::Molly)
+    }
+
+    /// Gets the total number of collisions so far
+    pub fn collisions(&self) -> usize {
+        self.collisions
+    }
+
+    /// Gets the simulated time
+    pub fn time(&self) -> f32 {
+        self.time as f32 * self.ant_step
+    }
+
+    /// Gets the seed that was used to generate the layout
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+}
+
+impl Default for ArenaBuilder {
+    fn default() -> Self {
+        Self {
+            common: Common::default(),
+            shape: Shape::default(),
+            directions: Directions::default(),
+            radius: 0.01,
+            ants: None,
+        }
+    }
+}
+
+impl ArenaBuilder {
+    common_setters!();
+
+    /// Sets the index of molly in the generated ants (by default molly is
+    /// the ant nearest to the center)
+    pub fn molly(mut self, index: usize) -> Self {
+        self.common.molly = Some(index);
+        self
+    }
+
+    /// Sets the shape of the arena (default is square)
+    pub fn shape(mut self, shape: Shape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets the directions in which the ants walk (default is cardinal)
+    pub fn directions(mut self, directions: Directions) -> Self {
+        self.directions = directions;
+        self
+    }
+
+    /// Sets the distance at which the ants meet (default is 0.01)
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets the exact layout of the ants, the count, molly, directions and
+    /// seed are ignored
+    pub fn ants(mut self, ants: Vec<ArenaAnt>) -> Self {
+        self.ants = Some(ants);
+        self
+    }
+
+    /// Creates the arena
+    pub fn build(&self) -> Result<Arena> {
+        self.common
+            .validate(self.common.molly.filter(|_| self.ants.is_none()))?;
+
+        if self.radius.is_nan() || self.radius <= 0. {
+            return Err(Report::msg(format!(
+                "Invalid radius {}, it must be positive",
+                self.radius
+            )));
+        }
+
+        let seed = self.common.seed();
+        let ants = match &self.ants {
+            Some(a) => a.clone(),
+            None => self.place_ants(&mut StdRng::seed_from_u64(seed)),
+        };
+
+        Ok(Arena {
+            ants,
+            ant_step: self.common.step,
+            time: 0,
+            shape: self.shape,
+            radius: self.radius,
+            exits: vec![],
+            collisions: 0,
+            seed,
+        })
+    }
+
+    /// Places the ants randomly in the arena
+    fn place_ants(&self, rng: &mut StdRng) -> Vec<ArenaAnt> {
+        let mut ants: Vec<_> = (0..self.common.count)
+            .map(|id| {
+                // generate the positions until they are inside the arena
+                let (x, y) = loop {
+                    let (x, y) =
+                        (rng.gen_range(0.0..1.), rng.gen_range(0.0..1.));
+                    if self.shape.contains(x, y) {
+                        break (x, y);
+                    }
+                };
+                let angle = match self.directions {
+                    Directions::Cardinal => {
+                        rng.gen_range(0..4) as f32 * TAU / 4.
+                    }
+                    Directions::Any => rng.gen_range(0.0..TAU),
+                };
+                let speed = self.common.speed(rng);
+                ArenaAnt {
+                    x,
+                    y,
+                    vx: (angle.cos() * speed * 1e6).round() / 1e6,
+                    vy: (angle.sin() * speed * 1e6).round() / 1e6,
+                    typ: AntType::Some,
+                    id,
+                }
+            })
+            .collect();
+
+        let center = |a: &ArenaAnt| (a.x - 0.5).powi(2) + (a.y - 0.5).powi(2);
+        let molly = self.common.molly.or_else(|| {
+            (0..ants.len())
+                .min_by(|i, j| center(&ants[*i]).total_cmp(&center(&ants[*j])))
+        });
+        if let Some(m) = molly {
+            ants[m].typ = AntType::Molly;
+        }
+
+        ants
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ant(x: f32, y: f32, vx: f32, vy: f32, id: usize) -> ArenaAnt {
+        ArenaAnt {
+            x,
+            y,
+            vx,
+            vy,
+            typ: AntType::Some,
+            id,
+        }
+    }
+
+    #[test]
+    fn head_on_ants_turn_around() {
+        let mut arena = Arena::builder()
+            .ants(vec![ant(0.4, 0.5, 1., 0., 0), ant(0.6, 0.5, -1., 0., 1)])
+            .build()
+            .unwrap();
+        while arena.has_ants() {
+            arena.step();
+        }
+
+        assert_eq!(arena.collisions(), 1);
+        let exits = arena.exits();
+        assert!(exits.iter().all(|e| (e.time - 0.6).abs() < 0.01));
+        assert!(exits.iter().any(|e| e.id == 0 && e.x < 0.));
+        assert!(exits.iter().any(|e| e.id == 1 && e.x >= 1.));
+    }
+
+    #[test]
+    fn perpendicular_ants_pass() {
+        // the ants meet at the center, but they don't walk against each other
+        let mut arena = Arena::builder()
+            .ants(vec![ant(0.4, 0.5, 1., 0., 0), ant(0.5, 0.4, 0., 1., 1)])
+            .build()
+            .unwrap();
+        while arena.has_ants() {
+            arena.step();
+        }
+
+        assert_eq!(arena.collisions(), 0);
+        let exits = arena.exits();
+        assert!(exits.iter().any(|e| e.id == 0 && e.x >= 1.));
+        assert!(exits.iter().any(|e| e.id == 1 && e.y >= 1.));
+    }
+
+    #[test]
+    fn oblique_ants_pass() {
+        // the ants cross at the center at 100 degrees
+        let (vx, vy) = (100f32.to_radians().cos(), 100f32.to_radians().sin());
+        let other = ant(0.5 - 0.1 * vx, 0.5 - 0.1 * vy, vx, vy, 1);
+        let mut arena = Arena::builder()
+            .ants(vec![ant(0.4, 0.5, 1., 0., 0), other])
+            .build()
+            .unwrap();
+        while arena.has_ants() {
+            arena.step();
+        }
+
+        assert_eq!(arena.collisions(), 0);
+        let exits = arena.exits();
+        assert!(exits.iter().any(|e| e.id == 0 && e.x >= 1.));
+        assert!(exits.iter().any(|e| e.id == 1 && e.y >= 1.));
+    }
+}
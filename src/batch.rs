@@ -0,0 +1,145 @@
+use std::{cmp::Ordering, thread};
+
+use eyre::Result;
+
+use crate::{AntRodBuilder, Fall};
+
+/// Runs `runs` independent simulations without drawing them and collects the
+/// falls of molly. Run `i` uses seed `seed + i` so that any of the runs can
+/// be repeated. Runs without molly are skipped.
+pub fn molly_falls(
+    conf: &AntRodBuilder,
+    runs: usize,
+    threads: usize,
+    seed: u64,
+) -> Result<Vec<Fall>> {
+    let threads = threads.max(1);
+
+    // each thread takes every `threads`-th run
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                s.spawn(move || {
+                    (t..runs)
+                        .step_by(threads)
+                        .map(|i| run_one(conf, seed.wrapping_add(i as u64)))
+                        .collect::<Result<Vec<_>>>()
+                })
+            })
+            .collect();
+
+        let mut res = vec![];
+        for h in handles {
+            let falls = h.join().expect("batch thread panicked")?;
+            res.extend(falls.into_iter().flatten());
+        }
+        Ok(res)
+    })
+}
+
+/// Runs single simulation until molly falls, returns [`None`] if there is
+/// no molly
+fn run_one(conf: &AntRodBuilder, seed: u64) -> Result<Option<Fall>> {
+    let mut sim = conf.clone().seed(seed).build()?;
+    while sim.molly_fall().is_none() && sim.has_ants() {
+        sim.step();
+    }
+    Ok(sim.molly_fall().copied())
+}
+
+/// Summary statistics of a set of samples
+#[derive(Clone, Copy, Debug)]
+pub struct Stats {
+    pub mean: f32,
+    pub median: f32,
+    pub min: f32,
+    pub max: f32,
+    pub variance: f32,
+}
+
+impl Stats {
+    /// Computes the statistics, sorts the samples. Returns [`None`] if there
+    /// are no samples.
+    pub fn new(samples: &mut [f32]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let len = samples.len();
+        let mean = samples.iter().sum::<f32>() / len as f32;
+        let variance =
+            samples.iter().map(|s| (s - mean) * (s - mean)).sum::<f32>()
+                / len as f32;
+        let median = if len.is_multiple_of(2) {
+            (samples[len / 2 - 1] + samples[len / 2]) / 2.
+        } else {
+            samples[len / 2]
+        };
+
+        Some(Self {
+            mean,
+            median,
+            min: samples[0],
+            max: samples[len - 1],
+            variance,
+        })
+    }
+
+    /// Counts the samples in `bins` bins of equal size between the minimum
+    /// and maximum
+    pub fn histogram(&self, samples: &[f32], bins: usize) -> Vec<usize> {
+        let bins = bins.max(1);
+        let size = self.bin_size(bins);
+        let mut counts = vec![0; bins];
+        for s in samples {
+            let bin = if size > 0. {
+                ((s - self.min) / size) as usize
+            } else {
+                0
+            };
+            counts[bin.min(bins - 1)] += 1;
+        }
+        counts
+    }
+
+    /// Gets the size of each bin of histogram with `bins` bins
+    pub fn bin_size(&self, bins: usize) -> f32 {
+        (self.max - self.min) / bins.max(1) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_and_variance() {
+        let stats = Stats::new(&mut [4., 1., 3., 2.]).unwrap();
+        assert_eq!(stats.median, 2.5);
+        assert_eq!((stats.min, stats.max), (1., 4.));
+        // population variance, divided by the number of samples
+        assert_eq!(stats.mean, 2.5);
+        assert_eq!(stats.variance, 1.25);
+
+        let stats = Stats::new(&mut [5., 1., 3.]).unwrap();
+        assert_eq!(stats.median, 3.);
+        assert!(Stats::new(&mut []).is_none());
+    }
+
+    #[test]
+    fn histogram_bins() {
+        let mut samples = [0., 1., 2., 3., 4.];
+        let stats = Stats::new(&mut samples).unwrap();
+        assert_eq!(stats.bin_size(4), 1.);
+        // the maximum is in the last bin
+        assert_eq!(stats.histogram(&samples, 4), [1, 1, 1, 2]);
+
+        // all the samples are the same, so the bins have no size
+        let mut samples = [2.; 3];
+        let stats = Stats::new(&mut samples).unwrap();
+        assert_eq!(stats.bin_size(5), 0.);
+        assert_eq!(stats.histogram(&samples, 5), [3, 0, 0, 0, 0]);
+    }
+}
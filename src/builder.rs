@@ -0,0 +1,121 @@
+//! Configuration shared by the builders of the simulations
+
+use eyre::{Report, Result};
+use rand::{thread_rng, Rng};
+
+/// The parameters that are the same for all the simulations
+#[derive(Clone, Debug)]
+pub(crate) struct Common {
+    pub count: usize,
+    // index of molly in the generated ants, the default depends on the
+    // simulation
+    pub molly: Option<usize>,
+    pub step: f32,
+    pub seed: Option<u64>,
+    // range of the random speeds
+    pub speeds: Option<(f32, f32)>,
+}
+
+impl Default for Common {
+    fn default() -> Self {
+        Self {
+            count: 25,
+            molly: None,
+            step: 0.001,
+            seed: None,
+            speeds: None,
+        }
+    }
+}
+
+impl Common {
+    /// Checks the step, the range of speeds and that `molly` (if the ants
+    /// are generated) is one of the generated ants
+    pub fn validate(&self, molly: Option<usize>) -> Result<()> {
+        if !(self.step.is_finite() && self.step > 0.) {
+            return Err(Report::msg(format!(
+                "Invalid step {}, it must be positive",
+                self.step
+            )));
+        }
+
+        if let Some(m) = molly.filter(|m| *m >= self.count) {
+            return Err(Report::msg(format!(
+                "Invalid molly index {m} out of {}",
+                self.count
+            )));
+        }
+
+        if let Some((min, max)) = self.speeds {
+            if !(min > 0. && min <= max && max.is_finite()) {
+                return Err(Report::msg(format!(
+                    "Invalid range of speeds {min}..{max}"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the seed, random one if it is not set
+    pub fn seed(&self) -> u64 {
+        self.seed.unwrap_or_else(|| thread_rng().gen())
+    }
+
+    /// Generates random speed from the range of speeds, 1 if there is no
+    /// range
+    pub fn speed(&self, rng: &mut impl Rng) -> f32 {
+        match self.speeds {
+            Some((min, max)) => rng.gen_range(min..=max),
+            None => 1.,
+        }
+    }
+}
+
+/// Implements the setters of [`Common`] for builder that has it in the field
+/// `common`
+macro_rules! common_setters {
+    () => {
+        /// Sets the total amount of ants (default is 25)
+        pub fn count(mut self, count: usize) -> Self {
+            self.common.count = count;
+            self
+        }
+
+        /// Sets how much the ants move with each step (default is 0.001)
+        pub fn step(mut self, step: f32) -> Self {
+            self.common.step = step;
+            self
+        }
+
+        /// Sets the seed for the random layout (random by default)
+        pub fn seed(mut self, seed: u64) -> Self {
+            self.common.seed = Some(seed);
+            self
+        }
+
+        /// Gives the randomly placed ants random speeds in the range
+        /// [`min`, `max`] (by default all the ants have speed 1)
+        pub fn speeds(mut self, min: f32, max: f32) -> Self {
+            self.common.speeds = Some((min, max));
+            self
+        }
+    };
+}
+
+pub(crate) use common_setters;
+
+#[cfg(test)]
+mod tests {
+    use crate::{arena::Arena, network::Network, AntRod};
+
+    #[test]
+    fn invalid_step_is_rejected() {
+        for step in [0., -0.001, f32::NAN, f32::INFINITY] {
+            assert!(AntRod::builder().step(step).build().is_err());
+            assert!(Arena::builder().step(step).build().is_err());
+            let graph = "y".parse().unwrap();
+            assert!(Network::builder(graph).step(step).build().is_err());
+        }
+    }
+}
@@ -0,0 +1,744 @@
+use std::{
+    cmp::Ordering,
+    env,
+    fmt::{self, Display},
+    io::{self, IsTerminal, Write},
+    str::FromStr,
+};
+
+use eyre::Report;
+
+use crate::{
+    arena::{ArenaAnt, Shape},
+    network::{Graph, NetAnt},
+    Ant, AntType, Event,
+};
+
+/// The characters used to draw the ants on the rod
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Glyphs {
+    /// `●` for all the ants
+    #[default]
+    Dot,
+    /// `◀` and `▶` by the direction of the ant
+    Triangle,
+    /// `<` and `>` by the direction of the ant
+    Ascii,
+}
+
+impl Glyphs {
+    /// Gets the character of ant that walks with the given speed, ant that
+    /// doesn't move has no direction
+    pub fn ant(&self, speed: f32) -> char {
+        match (self, speed.partial_cmp(&0.)) {
+            (Glyphs::Triangle, Some(Ordering::Greater)) => '▶',
+            (Glyphs::Triangle, Some(Ordering::Less)) => '◀',
+            (Glyphs::Ascii, Some(Ordering::Greater)) => '>',
+            (Glyphs::Ascii, Some(Ordering::Less)) => '<',
+            (Glyphs::Ascii, _) => 'o',
+            _ => '●',
+        }
+    }
+
+    /// Gets the character that marks a collision
+    pub fn collision(&self) -> char {
+        match self {
+            Glyphs::Ascii => '*',
+            _ => '✱',
+        }
+    }
+}
+
+impl FromStr for Glyphs {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(Glyphs::Dot),
+            "triangle" => Ok(Glyphs::Triangle),
+            "ascii" => Ok(Glyphs::Ascii),
+            _ => Err(Report::msg(format!("invalid glyphs {s}"))),
+        }
+    }
+}
+
+/// The colors used to draw the ants
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Theme {
+    /// Dark ants on white rod
+    #[default]
+    Light,
+    /// Bright ants on black rod
+    Dark,
+    /// Bold ants on bright white rod
+    HighContrast,
+    /// Colors that are distinguishable also with color blindness
+    /// (Okabe-Ito palette)
+    ColorBlind,
+    /// No colors at all
+    Plain,
+}
+
+impl Theme {
+    /// Gets the theme used when the user doesn't choose any, it is plain
+    /// when `NO_COLOR` is set or the output isn't terminal
+    pub fn detect() -> Self {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|c| !c.is_empty());
+        if no_color || !io::stdout().is_terminal() {
+            Theme::Plain
+        } else {
+            Theme::Light
+        }
+    }
+
+    /// Gets the color of the ant
+    pub fn color(&self, typ: AntType) -> &'static str {
+        let (ant, molly, tracked): (_, _, &[&str]) = match self {
+            // black, magenta and red, blue, green, cyan, yellow
+            Theme::Light => (
+                "\x1b[30m",
+                "\x1b[35m",
+                &["\x1b[31m", "\x1b[34m", "\x1b[32m", "\x1b[36m", "\x1b[33m"],
+            ),
+            // the bright variants of the colors
+            Theme::Dark => (
+                "\x1b[97m",
+                "\x1b[95m",
+                &["\x1b[91m", "\x1b[94m", "\x1b[92m", "\x1b[96m", "\x1b[93m"],
+            ),
+            // the light colors in bold
+            Theme::HighContrast => (
+                "\x1b[1;30m",
+                "\x1b[1;35m",
+                &[
+                    "\x1b[1;31m",
+                    "\x1b[1;34m",
+                    "\x1b[1;32m",
+                    "\x1b[1;36m",
+                    "\x1b[1;33m",
+                ],
+            ),
+            // black, vermillion and blue, orange, bluish green, reddish
+            // purple, sky blue
+            Theme::ColorBlind => (
+                "\x1b[38;2;0;0;0m",
+                "\x1b[38;2;213;94;0m",
+                &[
+                    "\x1b[38;2;0;114;178m",
+                    "\x1b[38;2;230;159;0m",
+                    "\x1b[38;2;0;158;115m",
+                    "\x1b[38;2;204;121;167m",
+                    "\x1b[38;2;86;180;233m",
+                ],
+            ),
+            Theme::Plain => return "",
+        };
+        match typ {
+            AntType::None => "",
+            AntType::Some => ant,
+            AntType::Molly => molly,
+            AntType::Tracked(n) => tracked[n % tracked.len()],
+        }
+    }
+
+    /// Gets the color of the additional information, such as the wrap
+    /// indicators
+    pub fn muted(&self) -> &'static str {
+        match self {
+            // dark gray
+            Theme::Light | Theme::ColorBlind => "\x1b[90m",
+            // light gray
+            Theme::Dark => "\x1b[37m",
+            // bold dark gray
+            Theme::HighContrast => "\x1b[1;90m",
+            Theme::Plain => "",
+        }
+    }
+
+    /// Gets the background of the rod
+    pub fn background(&self) -> &'static str {
+        match self {
+            Theme::Light => "\x1b[47m",
+            Theme::Dark => "\x1b[40m",
+            Theme::HighContrast => "\x1b[107m",
+            Theme::ColorBlind => "\x1b[48;2;255;255;255m",
+            Theme::Plain => "",
+        }
+    }
+
+    /// Gets the sequence that resets the colors
+    pub fn reset(&self) -> &'static str {
+        match self {
+            Theme::Plain => "",
+            _ => "\x1b[0m",
+        }
+    }
+
+    /// Gets the character of empty place on the rod, without colors the
+    /// rod has no background, so it is drawn with dots
+    pub fn empty(&self) -> char {
+        match self {
+            Theme::Plain => '.',
+            _ => ' ',
+        }
+    }
+
+    /// Writes the glyph with the color on the background of the rod
+    pub fn write(
+        &self,
+        f: &mut impl fmt::Write,
+        color: &str,
+        glyph: char,
+    ) -> fmt::Result {
+        write!(f, "{color}{}{glyph}{}", self.background(), self.reset())
+    }
+
+    /// Writes the ant with the glyph, empty place if there is no ant
+    fn write_ant(
+        &self,
+        f: &mut impl fmt::Write,
+        typ: AntType,
+        glyph: char,
+    ) -> fmt::Result {
+        let glyph = if typ == AntType::None {
+            self.empty()
+        } else {
+            glyph
+        };
+        self.write(f, self.color(typ), glyph)
+    }
+}
+
+impl FromStr for Theme {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "light" => Ok(Theme::Light),
+            "dark" => Ok(Theme::Dark),
+            "high-contrast" => Ok(Theme::HighContrast),
+            "colorblind" => Ok(Theme::ColorBlind),
+            "plain" => Ok(Theme::Plain),
+            _ => Err(Report::msg(format!("invalid theme {s}"))),
+        }
+    }
+}
+
+/// Draws the rod to the terminal
+pub struct Drawer {
+    ant_vec: Vec<AntType>,
+    buffer: String,
+    // total number of characters of each line
+    resolution: usize,
+    // shown in the status line
+    seed: u64,
+    // draw wrap indicators at the ends of the rod
+    ring: bool,
+    // don't overwrite the last frame, but print each frame on a new line
+    // together with its time so that the output is space-time diagram
+    spacetime: bool,
+    // number of drawn frames
+    frames: usize,
+    // additional text in the status line
+    status: String,
+    // the terminal is in raw mode, so new lines don't return the cursor
+    raw: bool,
+    // draw the ants as braille dots, each character has two columns of dots
+    braille: bool,
+    // number of ants in each column of dots
+    dots: Vec<[usize; 2]>,
+    glyphs: Glyphs,
+    // the speed of the shown ant in each character
+    speeds: Vec<f32>,
+    // positions of the collisions since the last frame
+    marks: Vec<f32>,
+    // the characters where the ants collided
+    collided: Vec<bool>,
+    theme: Theme,
+    // use only ascii characters
+    ascii: bool,
+    // print each frame after the last one instead of moving the cursor
+    append: bool,
+}
+
+impl Drawer {
+    /// Width of the time at the end of each line in the space-time diagram
+    const TIME_WIDTH: usize = 10;
+
+    /// Creates drawer that uses `resolution` characters for each line, the
+    /// `seed` is shown in the status line
+    pub fn new(resolution: usize, seed: u64) -> Self {
+        Self {
+            ant_vec: vec![],
+            buffer: String::new(),
+            resolution,
+            seed,
+            ring: false,
+            spacetime: false,
+            frames: 0,
+            status: String::new(),
+            raw: false,
+            braille: false,
+            dots: vec![],
+            glyphs: Glyphs::default(),
+            speeds: vec![],
+            marks: vec![],
+            collided: vec![],
+            theme: Theme::default(),
+            ascii: false,
+            append: false,
+        }
+    }
+
+    /// Ends the lines with `\r\n` so that the output is correct also when
+    /// the terminal is in raw mode
+    pub fn raw(mut self, raw: bool) -> Self {
+        self.raw = raw;
+        self
+    }
+
+    /// Sets additional text shown at the end of the status line
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+    }
+
+    /// Draws wrap indicators at the ends of the rod
+    pub fn ring(mut self, ring: bool) -> Self {
+        self.ring = ring;
+        self
+    }
+
+    /// Prints each frame on a new line with its time instead of overwriting
+    /// the last frame, so that the output is space-time diagram
+    pub fn spacetime(mut self, spacetime: bool) -> Self {
+        self.spacetime = spacetime;
+        self
+    }
+
+    /// Draws the ants as braille dots, so that the ants that are in the same
+    /// character are visible. Each character has two columns of dots, the
+    /// number of dots in a column is the number of ants in it. Below the rod
+    /// is the number of ants in each character where there are more of them.
+    pub fn braille(mut self, braille: bool) -> Self {
+        self.braille = braille;
+        self
+    }
+
+    /// Sets the characters used to draw the ants
+    pub fn glyphs(mut self, glyphs: Glyphs) -> Self {
+        self.glyphs = glyphs;
+        self
+    }
+
+    /// Sets the colors used to draw the ants
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Uses only ascii characters for the wrap indicators. The glyphs should
+    /// be [`Glyphs::Ascii`].
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Prints each frame after the last one instead of overwriting it, so
+    /// that the output is readable also in logs and dumb terminals
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+
+    /// Marks the collisions from the events in the next frame
+    pub fn mark(&mut self, events: &[Event]) {
+        self.marks.extend(events.iter().filter_map(|e| match e {
+            Event::Collision { position, .. } => Some(*position),
+            _ => None,
+        }));
+    }
+
+    /// Expects that `ants` is ordered by position
+    pub fn draw(&mut self, ants: &[Ant], time: f32) {
+        let wrap = if self.ascii { '~' } else { '↻' };
+        let nl = if self.raw { "\r\n" } else { "\n" };
+
+        self.ant_vec.clear();
+        self.ant_vec.resize(self.cells(), AntType::None);
+        self.dots.clear();
+        self.dots.resize(self.ant_vec.len(), [0; 2]);
+        self.speeds.clear();
+        self.speeds.resize(self.ant_vec.len(), 0.);
+        self.collided.clear();
+        self.collided.resize(self.ant_vec.len(), false);
+
+        // set the ants to their positions
+        let len = self.ant_vec.len();
+        let cell = |pos: f32| (pos as usize).min(len - 1);
+        for a in ants {
+            let pos = a.position * len as f32;
+            let c = cell(pos);
+            self.ant_vec[c].set(a.typ);
+            if self.ant_vec[c] == a.typ {
+                self.speeds[c] = a.speed;
+            }
+            let half = ((pos - c as f32) * 2.).clamp(0., 1.) as usize;
+            self.dots[c][half] += 1;
+        }
+        for m in self.marks.drain(..) {
+            self.collided[cell(m * len as f32)] = true;
+        }
+
+        // the line with the number of ants in each character
+        let density = self.braille && !self.spacetime;
+
+        self.buffer.clear();
+        if !self.spacetime && !self.append {
+            // move up to the first line and left, clear all from cursor to
+            // the end
+            let lines = if density { 3 } else { 2 };
+            self.buffer += &format!("\x1b[{lines}F\x1b[0J");
+        } else if self.frames == 0 {
+            self.buffer += &format!("seed: {}{nl}", self.seed);
+        }
+        self.frames += 1;
+
+        if self.ring {
+            _ = self.theme.write(&mut self.buffer, self.theme.muted(), wrap);
+        }
+        for (i, a) in self.ant_vec.iter().enumerate() {
+            if self.collided[i] {
+                // the ants may have already left the place of the collision
+                let color = self.theme.color(AntType::Some);
+                let glyph = self.glyphs.collision();
+                _ = self.theme.write(&mut self.buffer, color, glyph);
+                continue;
+            }
+            let glyph = if self.braille {
+                braille(self.dots[i])
+            } else {
+                self.glyphs.ant(self.speeds[i])
+            };
+            _ = self.theme.write_ant(&mut self.buffer, *a, glyph);
+        }
+        if self.ring {
+            _ = self.theme.write(&mut self.buffer, self.theme.muted(), wrap);
+        }
+
+        if density {
+            self.buffer += nl;
+            self.buffer += self.theme.muted();
+            if self.ring {
+                self.buffer.push(' ');
+            }
+            for d in &self.dots {
+                self.buffer.push(count_char(d[0] + d[1]));
+            }
+            self.buffer += self.theme.reset();
+        }
+
+        if self.spacetime {
+            print!(
+                "{} {:>w$.1}s{nl}",
+                self.buffer,
+                time * 100.0,
+                w = Self::TIME_WIDTH - 2
+            );
+            return;
+        }
+
+        print!(
+            "{}{nl}time: {:.1}s  seed: {}{}{nl}",
+            self.buffer,
+            time * 100.0,
+            self.seed,
+            self.status,
+        );
+    }
+
+    /// Gets the number of characters used for the rod
+    fn cells(&self) -> usize {
+        let mut cells = self.resolution;
+        // the wrap indicators take one character on each side
+        if self.ring {
+            cells = cells.saturating_sub(2);
+        }
+        if self.spacetime {
+            cells = cells.saturating_sub(Self::TIME_WIDTH);
+        }
+        cells.max(1)
+    }
+}
+
+/// Draws the two dimensional arena to the whole terminal
+pub struct ArenaDrawer {
+    cells: Vec<AntType>,
+    buffer: String,
+    width: usize,
+    // number of lines used for the arena, the last line is the status line
+    height: usize,
+    seed: u64,
+    shape: Shape,
+    // number of drawn frames
+    frames: usize,
+    theme: Theme,
+    // draw the ants with ascii characters
+    ascii: bool,
+    // print each frame after the last one instead of moving the cursor
+    append: bool,
+}
+
+impl ArenaDrawer {
+    /// Creates drawer that uses `width` characters for each line and
+    /// `height` lines including the status line, the `seed` is shown in the
+    /// status line
+    pub fn new(width: usize, height: usize, seed: u64, shape: Shape) -> Self {
+        Self {
+            cells: vec![],
+            buffer: String::new(),
+            width: width.max(1),
+            height: height.saturating_sub(1).max(1),
+            seed,
+            shape,
+            frames: 0,
+            theme: Theme::default(),
+            ascii: false,
+            append: false,
+        }
+    }
+
+    /// Sets the colors used to draw the ants
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Draws the ants with ascii characters
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Prints each frame below the last one instead of redrawing the whole
+    /// terminal
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+
+    /// Draws the ants over the last frame
+    pub fn draw(&mut self, ants: &[ArenaAnt], time: f32, collisions: usize) {
+        let (w, h) = (self.width, self.height);
+        self.cells.clear();
+        self.cells.resize(w * h, AntType::None);
+
+        // the y axis goes up
+        for a in ants {
+            let x = ((a.x * w as f32) as usize).min(w - 1);
+            let y = (((1. - a.y) * h as f32) as usize).min(h - 1);
+            self.cells[y * w + x].set(a.typ);
+        }
+
+        self.buffer.clear();
+        if self.append {
+            // end the status line of the last frame
+            if self.frames != 0 {
+                self.buffer.push('\n');
+            }
+        } else {
+            if self.frames == 0 {
+                // clear the screen
+                self.buffer += "\x1b[2J";
+            }
+            // move to the top left corner
+            self.buffer += "\x1b[H";
+        }
+        self.frames += 1;
+
+        for (i, a) in self.cells.iter().enumerate() {
+            if i != 0 && i % w == 0 {
+                self.buffer += "\n";
+            }
+            // the cells with center outside the arena are left empty
+            let (x, y) = (i % w, i / w);
+            let cx = (x as f32 + 0.5) / w as f32;
+            let cy = 1. - (y as f32 + 0.5) / h as f32;
+            if self.shape.contains(cx, cy) {
+                let glyph = if self.ascii { 'o' } else { '●' };
+                _ = self.theme.write_ant(&mut self.buffer, *a, glyph);
+            } else {
+                self.buffer.push(' ');
+            }
+        }
+
+        // clear the rest of the last status line
+        let clear = if self.append { "" } else { "\x1b[0K" };
+        print!(
+            "{}\n{clear}time: {:.1}s  seed: {}  collisions: {collisions}",
+            self.buffer,
+            time * 100.0,
+            self.seed,
+        );
+        // the status line doesn't end with new line so that the terminal
+        // doesn't scroll
+        _ = io::stdout().flush();
+    }
+}
+
+/// Draws the sticks of the graph to the terminal, each stick on its own line
+pub struct NetworkDrawer {
+    cells: Vec<AntType>,
+    buffer: String,
+    // total number of characters of each line
+    resolution: usize,
+    seed: u64,
+    // number of drawn frames
+    frames: usize,
+    theme: Theme,
+    // use only ascii characters
+    ascii: bool,
+    // print each frame after the last one instead of moving the cursor
+    append: bool,
+}
+
+impl NetworkDrawer {
+    /// Creates drawer that uses `resolution` characters for each line, the
+    /// `seed` is shown in the status line
+    pub fn new(resolution: usize, seed: u64) -> Self {
+        Self {
+            cells: vec![],
+            buffer: String::new(),
+            resolution,
+            seed,
+            frames: 0,
+            theme: Theme::default(),
+            ascii: false,
+            append: false,
+        }
+    }
+
+    /// Sets the colors used to draw the ants
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Draws the ants with ascii characters
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Prints each frame after the last one instead of overwriting it
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+
+    /// Draws the sticks with the ants over the last frame, each line starts
+    /// with the nodes of the stick
+    pub fn draw(&mut self, graph: &Graph, ants: &[NetAnt], time: f32) {
+        let sticks = graph.sticks();
+        // width of the node numbers
+        let nw = sticks
+            .iter()
+            .map(|s| s.from.max(s.to).to_string().len())
+            .max()
+            .unwrap_or(1);
+        let len = self.resolution.saturating_sub(2 * nw + 2).max(1);
+
+        self.cells.clear();
+        self.cells.resize(len * sticks.len(), AntType::None);
+        for a in ants {
+            let pos = ((a.position * len as f32) as usize).min(len - 1);
+            self.cells[a.stick * len + pos].set(a.typ);
+        }
+
+        self.buffer.clear();
+        if self.frames != 0 && !self.append {
+            // move to the first line of the last frame, clear all to the end
+            self.buffer += &format!("\x1b[{}F\x1b[0J", sticks.len() + 1);
+        }
+        self.frames += 1;
+
+        for (s, cells) in sticks.iter().zip(self.cells.chunks(len)) {
+            self.buffer += &format!("{:>nw$}-{:<nw$} ", s.from, s.to);
+            let glyph = if self.ascii { 'o' } else { '●' };
+            for a in cells {
+                _ = self.theme.write_ant(&mut self.buffer, *a, glyph);
+            }
+            self.buffer.push('\n');
+        }
+
+        println!(
+            "{}time: {:.1}s  seed: {}",
+            self.buffer,
+            time * 100.0,
+            self.seed,
+        );
+    }
+}
+
+impl AntType {
+    /// Sets the type of the cell, molly is shown over tracked ants and
+    /// tracked ants over the other ants
+    fn set(&mut self, other: AntType) {
+        match (&self, other) {
+            (AntType::Molly, AntType::Some | AntType::Tracked(_))
+            | (AntType::Tracked(_), AntType::Some) => {}
+            (_, a) => *self = a,
+        }
+    }
+}
+
+impl Display for AntType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Theme::default().write_ant(f, *self, '●')
+    }
+}
+
+/// Gets braille character with the given number of dots in its left and
+/// right column, the dots are filled from the bottom
+fn braille(dots: [usize; 2]) -> char {
+    // the bits of the dots from the bottom
+    const LEFT: [u32; 4] = [0x40, 0x04, 0x02, 0x01];
+    const RIGHT: [u32; 4] = [0x80, 0x20, 0x10, 0x08];
+
+    let bits: u32 = LEFT[..dots[0].min(4)]
+        .iter()
+        .chain(&RIGHT[..dots[1].min(4)])
+        .sum();
+    if bits == 0 {
+        return ' ';
+    }
+    char::from_u32(0x2800 + bits).unwrap_or('●')
+}
+
+/// Gets the character that shows the number of ants in one character of the
+/// rod, it is shown only when there are more of them
+fn count_char(count: usize) -> char {
+    match count {
+        0 | 1 => ' ',
+        n @ 2..=9 => char::from_digit(n as u32, 10).unwrap_or('+'),
+        _ => '+',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn braille_dots() {
+        assert_eq!(braille([0, 0]), ' ');
+        assert_eq!(braille([1, 0]), '⡀');
+        assert_eq!(braille([0, 1]), '⢀');
+        assert_eq!(braille([4, 4]), '⣿');
+        // there are only four dots in each column
+        assert_eq!(braille([7, 9]), '⣿');
+
+        assert_eq!(count_char(1), ' ');
+        assert_eq!(count_char(3), '3');
+        assert_eq!(count_char(12), '+');
+    }
+}
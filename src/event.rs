@@ -0,0 +1,303 @@
+use crate::{Ant, AntType, Collision, Event, Fall, Side};
+
+/// Distance under which two ants (or an ant and the end of the rod) are
+/// considered to touch
+pub(crate) const EPSILON: f32 = 1e-6;
+
+/// Event driven simulation of the ant rod. Instead of moving the ants by a
+/// fixed step, it computes when the next collision or fall happens and jumps
+/// directly to it, so the fall times are exact.
+pub struct EventRod {
+    // the vector is always ordered by position, the positions are valid at
+    // `time`
+    ants: Vec<Ant>,
+    time: f32,
+    fallen: Vec<Fall>,
+    collision: Collision,
+    // what happened during the last advance
+    events: Vec<Event>,
+    // total number of collisions
+    collisions: usize,
+}
+
+impl EventRod {
+    /// Creates new event driven simulation, expects that `ants` is ordered
+    /// by position
+    pub fn new(ants: Vec<Ant>) -> Self {
+        Self {
+            ants,
+            time: 0.,
+            fallen: vec![],
+            collision: Collision::default(),
+            events: vec![],
+            collisions: 0,
+        }
+    }
+
+    /// Sets what happens when two ants meet (default is that they both turn
+    /// around)
+    pub fn collision(mut self, collision: Collision) -> Self {
+        self.collision = collision;
+        self
+    }
+
+    /// Gets the time remaining to the next collision or fall, [`None`] if
+    /// nothing will ever happen
+    pub fn next_event(&self) -> Option<f32> {
+        let mut dt =
+            next_collision(&self.ants, false).unwrap_or(f32::INFINITY);
+
+        // only the outer ants can fall, the others would hit them first
+        if let Some(a) = self.ants.first().filter(|a| a.speed < 0.) {
+            dt = dt.min(a.position / -a.speed);
+        }
+        if let Some(a) = self.ants.last().filter(|a| a.speed > 0.) {
+            dt = dt.min((1. - a.position) / a.speed);
+        }
+
+        dt.is_finite().then_some(dt.max(0.))
+    }
+
+    /// Resolves all the events up to the given time and moves the ants to
+    /// their positions in that time
+    pub fn advance_to(&mut self, time: f32) {
+        self.events.clear();
+        while let Some(dt) = self.next_event() {
+            if self.time + dt > time {
+                break;
+            }
+            self.move_by(dt);
+            self.resolve();
+        }
+
+        if time > self.time {
+            self.move_by(time - self.time);
+            self.resolve();
+        }
+
+        self.collisions += count_collisions(&self.events);
+    }
+
+    pub fn has_ants(&self) -> bool {
+        !self.ants.is_empty()
+    }
+
+    /// Gets the ants on the rod ordered by position
+    pub fn ants(&self) -> &[Ant] {
+        &self.ants
+    }
+
+    /// Gets the simulated time
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Gets the ants that have already fallen, in the order in which they
+    /// fell
+    pub fn fallen(&self) -> &[Fall] {
+        &self.fallen
+    }
+
+    /// Gets the total number of collisions so far
+    pub fn collisions(&self) -> usize {
+        self.collisions
+    }
+
+    /// Gets what happened during the last [`EventRod::advance_to`]
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    fn move_by(&mut self, dt: f32) {
+        for a in &mut self.ants {
+            a.position += a.speed * dt;
+        }
+        self.time += dt;
+    }
+
+    /// Handles collisions and falls of ants that touch right now
+    fn resolve(&mut self) {
+        collide(
+            &mut self.ants,
+            self.collision,
+            false,
+            self.time,
+            &mut self.events,
+        );
+
+        // remove from the end
+        while let Some(a) = self
+            .ants
+            .last()
+            .filter(|a| a.speed > 0. && a.position >= 1. - EPSILON)
+        {
+            let fall = Fall {
+                id: a.id,
+                typ: a.typ,
+                side: Side::Right,
+                time: self.time,
+            };
+            self.fallen.push(fall);
+            self.events.push(Event::Fall(fall));
+            self.ants.pop();
+        }
+
+        // remove from the front
+        let cnt = self
+            .ants
+            .iter()
+            .position(|a| a.speed > 0. || a.position > EPSILON)
+            .unwrap_or(self.ants.len());
+        for a in self.ants.drain(..cnt) {
+            let fall = Fall {
+                id: a.id,
+                typ: a.typ,
+                side: Side::Left,
+                time: self.time,
+            };
+            self.fallen.push(fall);
+            self.events.push(Event::Fall(fall));
+        }
+    }
+}
+
+/// Gets the time remaining to the next collision of neighbouring ants,
+/// expects that `ants` is ordered by position. On `ring` the last ant is
+/// neighbour of the first one.
+pub(crate) fn next_collision(ants: &[Ant], ring: bool) -> Option<f32> {
+    let mut dt = f32::INFINITY;
+
+    // neighbours that approach each other
+    for w in ants.windows(2) {
+        if w[0].speed > w[1].speed {
+            dt = dt.min(
+                (w[1].position - w[0].position) / (w[0].speed - w[1].speed),
+            );
+        }
+    }
+
+    if let (true, [first, .., last]) = (ring, ants) {
+        if last.speed > first.speed {
+            dt = dt.min(
+                (first.position + 1. - last.position)
+                    / (last.speed - first.speed),
+            );
+        }
+    }
+
+    dt.is_finite().then_some(dt.max(0.))
+}
+
+/// Applies `collision` to all the neighbouring ants that touch and approach
+/// each other and records the collisions to `events`, expects that `ants`
+/// is ordered by position. On `ring` the last ant is neighbour of the first
+/// one.
+pub(crate) fn collide(
+    ants: &mut [Ant],
+    collision: Collision,
+    ring: bool,
+    time: f32,
+    events: &mut Vec<Event>,
+) {
+    for i in 1..ants.len() {
+        let (l, r) = ants.split_at_mut(i);
+        let (a, b) = (&mut l[i - 1], &mut r[0]);
+        if b.position - a.position <= EPSILON && a.speed > b.speed {
+            let position = (a.position + b.position) / 2.;
+            apply(collision, a, b, time, position, events);
+        }
+    }
+
+    if let (true, [first, .., last]) = (ring, &mut *ants) {
+        if first.position + 1. - last.position <= EPSILON
+            && last.speed > first.speed
+        {
+            // the positions may be just over the end of the ring
+            let position = first.position.rem_euclid(1.);
+            apply(collision, last, first, time, position, events);
+        }
+    }
+}
+
+/// Applies `collision` to ants that touch at `position`, `a` is on the
+/// left, and records it to `events`
+pub(crate) fn apply(
+    collision: Collision,
+    a: &mut Ant,
+    b: &mut Ant,
+    time: f32,
+    position: f32,
+    events: &mut Vec<Event>,
+) {
+    let speeds = [a.speed, b.speed];
+    if a.speed == 0. || b.speed == 0. {
+        // ant waiting at the end of the rod is like a wall
+        a.speed = -a.speed;
+        b.speed = -b.speed;
+    } else {
+        collision.apply(a, b);
+    }
+    events.push(Event::Collision {
+        left: a.id,
+        right: b.id,
+        time,
+        position,
+    });
+
+    for (ant, old) in [&*a, &*b].into_iter().zip(speeds) {
+        if ant.typ == AntType::Molly && ant.speed * old < 0. {
+            events.push(Event::MollyTurned { time, position });
+        }
+    }
+}
+
+/// Counts the collisions in `events`
+pub(crate) fn count_collisions(events: &[Event]) -> usize {
+    events
+        .iter()
+        .filter(|e| matches!(e, Event::Collision { .. }))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ants_meet_in_the_middle() {
+        let ant = |position, speed, id| Ant {
+            position,
+            speed,
+            typ: AntType::Some,
+            id,
+        };
+        let mut sim = EventRod::new(vec![ant(0.25, 1., 0), ant(0.75, -1., 1)]);
+        assert_eq!(sim.next_event(), Some(0.25));
+
+        sim.advance_to(10.);
+        let fall = |id, side| {
+            Event::Fall(Fall {
+                id,
+                typ: AntType::Some,
+                side,
+                time: 0.75,
+            })
+        };
+        assert_eq!(
+            sim.events(),
+            [
+                Event::Collision {
+                    left: 0,
+                    right: 1,
+                    time: 0.25,
+                    position: 0.5,
+                },
+                fall(1, Side::Right),
+                fall(0, Side::Left),
+            ]
+        );
+        assert_eq!(sim.collisions(), 1);
+        assert!(!sim.has_ants());
+        assert_eq!(sim.next_event(), None);
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+
+use crate::AntRod;
+
+/// Snapshots of the simulation so that it can be viewed again at any of the
+/// earlier times. When the history is rewound and the simulation changes,
+/// the newer snapshots are discarded.
+pub struct History {
+    frames: VecDeque<AntRod>,
+    // index of the current frame in `frames`
+    current: usize,
+    // maximum number of frames, the oldest frames are dropped
+    limit: usize,
+}
+
+impl History {
+    /// Starts the history at the given state of the simulation, at most
+    /// `limit` frames are kept
+    pub fn new(sim: AntRod, limit: usize) -> Self {
+        Self {
+            frames: VecDeque::from([sim]),
+            current: 0,
+            limit: limit.max(1),
+        }
+    }
+
+    /// Gets the current state of the simulation
+    pub fn current(&self) -> &AntRod {
+        &self.frames[self.current]
+    }
+
+    /// Gets the current state of the simulation for modification, the newer
+    /// frames are discarded
+    pub fn current_mut(&mut self) -> &mut AntRod {
+        self.frames.truncate(self.current + 1);
+        &mut self.frames[self.current]
+    }
+
+    /// Adds new frame after the current frame and moves to it, the newer
+    /// frames are discarded
+    pub fn push(&mut self, sim: AntRod) {
+        self.frames.truncate(self.current + 1);
+        self.frames.push_back(sim);
+        if self.frames.len() > self.limit {
+            self.frames.pop_front();
+        }
+        self.current = self.frames.len() - 1;
+    }
+
+    /// Moves to the previous frame, returns `false` if there is none
+    pub fn back(&mut self) -> bool {
+        if self.current == 0 {
+            return false;
+        }
+        self.current -= 1;
+        true
+    }
+
+    /// Moves to the next recorded frame, returns `false` if the current
+    /// frame is the latest
+    pub fn forward(&mut self) -> bool {
+        if self.is_latest() {
+            return false;
+        }
+        self.current += 1;
+        true
+    }
+
+    /// Moves to the oldest frame
+    pub fn first(&mut self) {
+        self.current = 0;
+    }
+
+    /// Moves to the latest frame
+    pub fn last(&mut self) {
+        self.current = self.frames.len() - 1;
+    }
+
+    /// Moves to the last frame with time at most `time` (or to the oldest
+    /// frame if all are later)
+    pub fn seek(&mut self, time: f32) {
+        let after = self.frames.partition_point(|f| f.time() <= time);
+        self.current = after.saturating_sub(1);
+    }
+
+    /// Checks whether the current frame is the latest
+    pub fn is_latest(&self) -> bool {
+        self.current + 1 == self.frames.len()
+    }
+
+    /// Gets the number of frames after the current frame
+    pub fn ahead(&self) -> usize {
+        self.frames.len() - self.current - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewind_and_replace() {
+        let sim = AntRod::builder().count(5).seed(7).build().unwrap();
+        let mut history = History::new(sim, 3);
+        for _ in 0..4 {
+            let mut next = history.current().clone();
+            next.step();
+            history.push(next);
+        }
+        // the oldest frames were dropped
+        history.first();
+        assert_eq!(history.ahead(), 2);
+        assert!((history.current().time() - 0.002).abs() < 1e-6);
+
+        history.seek(0.0035);
+        assert_eq!(history.ahead(), 1);
+        history.current_mut().step();
+        assert!(history.is_latest());
+    }
+}
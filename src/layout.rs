@@ -0,0 +1,159 @@
+use std::{f32::consts::TAU, str::FromStr};
+
+use eyre::{Report, Result};
+use rand::Rng;
+
+/// How the random positions of the ants are distributed on the rod
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Positions {
+    /// Anywhere on the rod with the same probability
+    #[default]
+    Uniform,
+    /// Around the given number of random centers
+    Clustered(usize),
+    /// Normal distribution around the center of the rod with the given
+    /// standard deviation
+    Gaussian(f32),
+    /// Evenly spaced, each ant is moved randomly by at most the given part
+    /// of the space between the ants
+    Even(f32),
+    /// Uniformly in the two halves of the rod separated by gap of the given
+    /// size in the center
+    Halves(f32),
+}
+
+impl Positions {
+    /// The width of the clusters
+    const CLUSTER_SIZE: f32 = 0.03;
+
+    /// Checks that the parameter of the distribution is valid
+    pub fn validate(&self) -> Result<()> {
+        let valid = match *self {
+            Positions::Uniform => true,
+            Positions::Clustered(c) => c > 0,
+            Positions::Gaussian(s) => s > 0. && s.is_finite(),
+            Positions::Even(j) | Positions::Halves(j) => {
+                (0. ..1.).contains(&j)
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(Report::msg(format!("Invalid distribution {self:?}")))
+        }
+    }
+
+    /// Generates `count` positions in the range [0, 1), they are not
+    /// ordered
+    pub fn sample(&self, rng: &mut impl Rng, count: usize) -> Vec<f32> {
+        match *self {
+            Positions::Uniform => {
+                (0..count).map(|_| rng.gen_range(0.0..1.)).collect()
+            }
+            Positions::Clustered(c) => {
+                let centers: Vec<f32> =
+                    (0..c).map(|_| rng.gen_range(0.0..1.)).collect();
+                (0..count)
+                    .map(|_| {
+                        let center = centers[rng.gen_range(0..c)];
+                        let pos = center + Self::CLUSTER_SIZE * normal(rng);
+                        clamp_to_rod(pos)
+                    })
+                    .collect()
+            }
+            Positions::Gaussian(sigma) => (0..count)
+                .map(|_| loop {
+                    // the positions outside the rod are generated again
+                    let pos = 0.5 + sigma * normal(rng);
+                    if (0. ..1.).contains(&pos) {
+                        break pos;
+                    }
+                })
+                .collect(),
+            Positions::Even(jitter) => {
+                let space = 1. / count as f32;
+                (0..count)
+                    .map(|i| {
+                        let shift = rng.gen_range(-0.5..=0.5) * jitter;
+                        clamp_to_rod((i as f32 + 0.5 + shift) * space)
+                    })
+                    .collect()
+            }
+            Positions::Halves(gap) => {
+                let half = (1. - gap) / 2.;
+                (0..count)
+                    .map(|_| {
+                        let pos = rng.gen_range(0.0..2. * half);
+                        if pos < half {
+                            pos
+                        } else {
+                            pos + gap
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl FromStr for Positions {
+    type Err = Report;
+
+    /// Parses the name of the distribution optionally followed by `:` and
+    /// its parameter, e.g. `gaussian:0.1`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, param) = match s.split_once(':') {
+            Some((n, p)) => (n, Some(p)),
+            None => (s, None),
+        };
+        let param = |default: f32| -> Result<f32> {
+            Ok(param.map(|p| p.parse()).transpose()?.unwrap_or(default))
+        };
+
+        let res = match name {
+            "uniform" => Positions::Uniform,
+            "clustered" => Positions::Clustered(param(3.)? as usize),
+            "gaussian" => Positions::Gaussian(param(0.15)?),
+            "even" => Positions::Even(param(0.5)?),
+            "halves" => Positions::Halves(param(0.2)?),
+            _ => {
+                return Err(Report::msg(format!(
+                    "invalid distribution of positions {s}"
+                )))
+            }
+        };
+        res.validate()?;
+        Ok(res)
+    }
+}
+
+/// Generates number from the standard normal distribution
+fn normal(rng: &mut impl Rng) -> f32 {
+    // Box-Muller transform
+    let u: f32 = 1. - rng.gen_range(0.0..1.);
+    let v: f32 = rng.gen_range(0.0..1.);
+    (-2. * u.ln()).sqrt() * (TAU * v).cos()
+}
+
+/// Moves positions outside of the rod to its nearest end
+fn clamp_to_rod(pos: f32) -> f32 {
+    pos.clamp(0., 1. - f32::EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn positions_are_on_rod() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for s in ["uniform", "clustered:4", "gaussian:0.5", "even", "halves"] {
+            let positions: Positions = s.parse().unwrap();
+            let sample = positions.sample(&mut rng, 1000);
+            assert_eq!(sample.len(), 1000);
+            assert!(sample.iter().all(|p| (0. ..1.).contains(p)), "{s}");
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Simulation of a math problem: ants walk on a rod, when two ants meet they
+//! both turn around and when an ant walks over an end of the rod it falls.
+//! When does Molly, one of the ants, fall?
+//!
+//! ```
+//! use stick_ants::AntRod;
+//!
+//! let mut sim = AntRod::builder().count(10).seed(42).build().unwrap();
+//! while sim.has_ants() {
+//!     sim.step();
+//! }
+//! let molly = sim.molly_fall().unwrap();
+//! println!("molly fell {} at {}", molly.side, molly.time);
+//! ```
+
+mod ant;
+pub mod arena;
+pub mod batch;
+mod builder;
+#[cfg(feature = "draw")]
+pub mod drawer;
+pub mod event;
+pub mod history;
+pub mod layout;
+pub mod network;
+pub mod record;
+mod rod;
+pub mod scenario;
+pub mod solver;
+pub mod summary;
+pub mod svg;
+
+pub use ant::{Ant, AntType, Boundary, Collision, Event, Fall, Side};
+pub use rod::{AntRod, AntRodBuilder};
@@ -1,8 +1,37 @@
-use std::{cmp::Ordering, iter, thread, time::Duration, env};
+use std::{
+    env, fs,
+    io::{self, IsTerminal},
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
+};
 
-use eyre::{Report, Result};
+use crossterm::{
+    event::{
+        self, Event as TermEvent, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers,
+    },
+    terminal,
+};
+use eyre::{Report, Result, WrapErr};
 use rand::{thread_rng, Rng};
 
+use stick_ants::{
+    arena::{Arena, ArenaBuilder, Directions, Shape},
+    batch::{self, Stats},
+    drawer::{ArenaDrawer, Drawer, Glyphs, NetworkDrawer, Theme},
+    event::EventRod,
+    history::History,
+    layout::Positions,
+    network::{Graph, Junction, Network, NetworkBuilder},
+    record::Record,
+    scenario, solver,
+    summary::{Results, Snapshot, Summary},
+    svg::Recorder,
+    Ant, AntRod, AntRodBuilder, AntType, Boundary, Collision, Event, Fall,
+    Side,
+};
+
 fn main() -> Result<()> {
     // simulation parameters
 
@@ -13,237 +42,705 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.batch {
+        return run_batch(&args);
+    }
+
+    if args.sweep {
+        return run_sweep(&args);
+    }
+
+    if let Some(shape) = args.arena {
+        return run_arena(args.arena_builder(shape).build()?, &args);
+    }
+
+    if let Some(graph) = &args.graph {
+        return run_network(args.network_builder(graph).build()?, &args);
+    }
+
     // create simulation
-    let mut sim = AntRod::from_args(&args);
+    let mut sim = args.builder().build()?;
 
-    let sleep = Duration::from_millis(args.sleep);
+    if args.solve {
+        return solve(&sim, &args);
+    }
 
-    // run the simulation
-    sim.draw();
-    while sim.has_ants() {
-        thread::sleep(sleep);
-        sim.step();
-        sim.draw();
+    let mut recorder = args.svg.as_ref().map(|_| Recorder::new(sim.ants()));
+
+    // save the record already at the start, so that there is record also
+    // when the run is interrupted
+    let mut record =
+        args.record.as_ref().map(|_| Record::new(&sim, args.exact));
+    if let (Some(r), Some(path)) = (&record, &args.record) {
+        r.save(path)?;
+    }
+
+    // when the ants may pass through each other (with the same speeds or
+    // exchange), no ant can stay on the rod longer than it takes the slowest
+    // ant to walk the whole rod, from the wall it has to walk back
+    let passing = sim.same_speeds() || sim.collision() == Collision::Exchange;
+    let longest = passing.then(|| {
+        let mut longest = sim
+            .ants()
+            .iter()
+            .map(|a| 1. / a.speed.abs())
+            .fold(0., f32::max);
+        for b in [args.left, args.right] {
+            match b {
+                Boundary::Reflecting => longest *= 2.,
+                Boundary::Sticky(t) => longest = longest * 2. + t,
+                _ => {}
+            }
+        }
+        longest
+    });
+
+    let events = if args.interactive {
+        run_interactive(sim, &mut recorder, &args)?;
+        vec![]
+    } else if args.events {
+        run_events(sim, recorder.as_mut(), &args)?
+    } else if args.no_draw {
+        run_headless(sim, recorder.as_mut(), longest, &args)?
+    } else if args.exact {
+        run_exact(
+            EventRod::new(sim.ants().to_vec()).collision(args.collision),
+            &mut args.drawer(sim.seed()),
+            recorder.as_mut(),
+            longest,
+            &args,
+        )
+    } else {
+        // run the simulation
+        let sleep = Duration::from_millis(args.sleep);
+        let every = args.steps_per_frame();
+        let mut drawer = args.drawer(sim.seed());
+        let mut log = vec![];
+        drawer.draw(sim.ants(), sim.time());
+        while sim.has_ants() {
+            thread::sleep(sleep);
+            let events = step(&mut sim, recorder.as_mut(), every);
+            drawer.mark(&events);
+            drawer.draw(sim.ants(), sim.time());
+            log.extend(events);
+        }
+
+        for f in sim.fallen() {
+            if matches!(f.typ, AntType::Tracked(_)) {
+                print_fall(f, args.theme);
+            }
+        }
+        print_summary(
+            &Summary::new(sim.fallen(), sim.collisions()),
+            longest,
+            args.theme,
+        );
+        log
+    };
+
+    if let (Some(r), Some(path)) = (&mut record, &args.record) {
+        r.events = events;
+        r.save(path)?;
+    } else if let (Some(r), false) = (&args.replay, args.interactive) {
+        r.check(&events)?;
+    }
+
+    if let (Some(r), Some(path)) = (recorder, &args.svg) {
+        fs::write(path, r.to_svg())
+            .wrap_err_with(|| format!("Failed to write svg to '{path}'"))?;
     }
 
     Ok(())
 }
 
-// the simulation structure
-struct AntRod {
-    // the vector is always ordered by position
-    ants: Vec<Ant>,
-    ant_step: f32,
-    drawer: Drawer,
-    time: usize,
+/// Moves the simulation by `count` steps or until there are no ants, records
+/// each step and returns the events of all the steps
+fn step(
+    sim: &mut AntRod,
+    mut recorder: Option<&mut Recorder>,
+    count: usize,
+) -> Vec<Event> {
+    let mut events = vec![];
+    for _ in 0..count {
+        if !sim.has_ants() {
+            break;
+        }
+        sim.step();
+        if let Some(r) = &mut recorder {
+            r.record(sim.ants(), sim.events(), sim.time());
+        }
+        events.extend_from_slice(sim.events());
+    }
+    events
 }
 
-impl AntRod {
-    /// Creates new simulation of ant rod,
-    /// Resolution is the resolution of the drawn output, ant_step is how much
-    /// the ants step with each simulatino step
-    fn from_args(args: &Args) -> Self {
-        // create vector of ants on the rod
-        let mut ants = Vec::new();
-        ants.reserve(args.ant_count);
-        ants.extend(iter::from_fn(|| Some(Ant::default())).take(args.ant_count));
-
-        // random positions
-
-        if args.regular {
-            // regular spacing with ants facing the furtherer side and molly in
-            // center
-            let dis = 1. / (args.ant_count as f32 + 1.);
-
-            // ants on the left
-            for i in 0..(args.ant_count / 2) {
-                ants[i] = Ant {
-                    position: dis * i as f32 + dis,
-                    speed: 1.,
-                    typ: AntType::Some,
-                };
-            }
+/// Runs the simulation without drawing and prints the results (or the
+/// snapshots of the ants) in the chosen format
+fn run_headless(
+    mut sim: AntRod,
+    mut recorder: Option<&mut Recorder>,
+    longest: Option<f32>,
+    args: &Args,
+) -> Result<Vec<Event>> {
+    let seed = sim.seed();
+    if args.snapshots && args.format == Format::Csv {
+        println!("{}", Snapshot::CSV_HEADER);
+    }
+    let snapshot = |ants: &[Ant], time: f32| -> Result<()> {
+        let snapshot = Snapshot { time, ants };
+        match args.format {
+            _ if !args.snapshots => {}
+            Format::Json => println!("{}", serde_json::to_string(&snapshot)?),
+            _ => print!("{}", snapshot.to_csv()),
+        }
+        Ok(())
+    };
 
-            // molly
-            ants[args.ant_count / 2] = Ant {
-                position: 0.5,
-                speed: 1.,
-                typ: AntType::Molly,
+    let every = args.sample.unwrap_or(1);
+    snapshot(sim.ants(), sim.time())?;
+    let mut log = vec![];
+    let (fallen, collisions) = if args.exact {
+        let mut sim =
+            EventRod::new(sim.ants().to_vec()).collision(args.collision);
+        let step = args.ant_step * every as f32;
+        let mut frame = 0;
+        while sim.has_ants() {
+            frame += 1;
+            // without snapshots jump from one event to the next one
+            let time = match (args.snapshots, sim.next_event()) {
+                (true, _) => frame as f32 * step,
+                (false, Some(dt)) => sim.time() + dt,
+                (false, None) => break,
             };
-
-            // ants on the right
-            for i in (args.ant_count / 2 + 1)..args.ant_count {
-                ants[i] = Ant {
-                    position: dis * i as f32 + dis,
-                    speed: -1.,
-                    typ: AntType::Some,
-                };
+            sim.advance_to(time);
+            if let Some(r) = &mut recorder {
+                r.record(sim.ants(), sim.events(), sim.time());
             }
-        } else {
-            // random positions
-            ants.sort_by(|a, b| {
-                a.position
-                    .partial_cmp(&b.position)
-                    .unwrap_or(Ordering::Equal)
-            });
-            ants[args.molly_index].typ = AntType::Molly;
+            log.extend_from_slice(sim.events());
+            snapshot(sim.ants(), sim.time())?;
         }
+        (sim.fallen().to_vec(), sim.collisions())
+    } else {
+        while sim.has_ants() {
+            log.extend(step(&mut sim, recorder.as_deref_mut(), every));
+            snapshot(sim.ants(), sim.time())?;
+        }
+        (sim.fallen().to_vec(), sim.collisions())
+    };
+
+    if args.snapshots {
+        return Ok(log);
+    }
 
-        Self {
-            ants,
-            ant_step: args.ant_step,
-            drawer: Drawer::new(args.resolution),
-            time: 0,
+    let res = Results {
+        seed,
+        summary: Summary::new(&fallen, collisions),
+        falls: &fallen,
+    };
+    match args.format {
+        Format::Text => {
+            for f in &fallen {
+                print_fall(f, args.theme);
+            }
+            print_summary(&res.summary, longest, args.theme);
         }
+        Format::Json => println!("{}", serde_json::to_string(&res)?),
+        Format::Csv => print!("{}", res.to_csv()),
     }
 
-    fn step(&mut self) {
-        // update positions
-        for a in &mut self.ants {
-            a.position += a.speed * self.ant_step;
+    Ok(log)
+}
+
+/// Runs the simulation without drawing and prints everything that happens
+/// as json lines
+fn run_events(
+    mut sim: AntRod,
+    mut recorder: Option<&mut Recorder>,
+    args: &Args,
+) -> Result<Vec<Event>> {
+    let mut log = vec![];
+    let mut print = |events: &[Event]| -> Result<()> {
+        for e in events {
+            println!("{}", serde_json::to_string(e)?);
         }
+        log.extend_from_slice(events);
+        Ok(())
+    };
 
-        // sort by position, but retain types
-        let typ: Vec<_> = self.ants.iter().map(|a| a.typ).collect();
-        self.ants.sort_by(|a, b| {
-            a.position
-                .partial_cmp(&b.position)
-                .unwrap_or(Ordering::Equal)
-        });
-        for (a, t) in self.ants.iter_mut().zip(typ.iter()) {
-            a.typ = *t;
+    if !args.exact {
+        while sim.has_ants() {
+            sim.step();
+            if let Some(r) = &mut recorder {
+                r.record(sim.ants(), sim.events(), sim.time());
+            }
+            print(sim.events())?;
         }
+        return Ok(log);
+    }
 
-        // remove those that have fallen
+    // jump directly from one event to the next one
+    let mut sim = EventRod::new(sim.ants().to_vec()).collision(args.collision);
+    while let Some(dt) = sim.next_event() {
+        sim.advance_to(sim.time() + dt);
+        if let Some(r) = &mut recorder {
+            r.record(sim.ants(), sim.events(), sim.time());
+        }
+        print(sim.events())?;
+    }
+    Ok(log)
+}
 
-        // remove from the end
-        while self
-            .ants
-            .last()
-            .map(|a| a.position >= 1.)
-            .unwrap_or_default()
-        {
-            self.ants.pop();
+/// Runs the simulation controlled by the keyboard until the user quits.
+/// The shown frames are kept so that the user can go back in time.
+fn run_interactive(
+    sim: AntRod,
+    recorder: &mut Option<Recorder>,
+    args: &Args,
+) -> Result<()> {
+    // about 10 MB with the default number of ants
+    const HISTORY_LIMIT: usize = 20_000;
+
+    let _raw = RawMode::enable()?;
+
+    let every = args.steps_per_frame();
+    let mut sleep = args.sleep;
+    let mut paused = false;
+    let mut drawer = args.drawer(sim.seed()).raw(true);
+    let mut history = History::new(sim, HISTORY_LIMIT);
+    // time typed by the user to jump to
+    let mut goto: Option<String> = None;
+
+    loop {
+        let sim = history.current();
+        let mut status =
+            format!("  step: {}  delay: {sleep}ms", sim.ant_step());
+        if !history.is_latest() {
+            status += &format!("  history: -{}", history.ahead());
         }
+        if paused {
+            status += "  paused";
+        }
+        if let Some(g) = &goto {
+            status += &format!("  go to: {g}s");
+        }
+        drawer.set_status(status);
+        // the frame in the history keeps the events of its last step
+        drawer.mark(sim.events());
+        drawer.draw(sim.ants(), sim.time());
 
-        // remove from the front
-        self.ants.drain(
-            0..self
-                .ants
-                .iter()
-                .position(|a| a.position >= 0.)
-                .unwrap_or(self.ants.len()),
-        );
+        // when paused or finished wait only for the user
+        let running = !paused && sim.has_ants();
+        let timeout = running.then(|| Duration::from_millis(sleep));
+        let Some(key) = read_key(timeout)? else {
+            advance(&mut history, recorder.as_mut(), every);
+            continue;
+        };
 
-        self.time += 1;
+        if let Some(g) = &mut goto {
+            match key.code {
+                KeyCode::Char(c) if c.is_ascii_digit() || c == '.' => {
+                    g.push(c)
+                }
+                KeyCode::Backspace => _ = g.pop(),
+                KeyCode::Enter => {
+                    // the shown time is in hundreds of the simulation time
+                    if let Ok(t) = g.parse::<f32>() {
+                        paused = true;
+                        seek(&mut history, recorder.as_mut(), every, t / 100.);
+                    }
+                    goto = None;
+                }
+                KeyCode::Esc => goto = None,
+                _ => {}
+            }
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Char('c')
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                break
+            }
+            KeyCode::Char(' ') => paused = !paused,
+            KeyCode::Char('.') | KeyCode::Right => {
+                paused = true;
+                advance(&mut history, recorder.as_mut(), every);
+            }
+            KeyCode::Char(',') | KeyCode::Left => {
+                paused = true;
+                history.back();
+            }
+            KeyCode::Home => {
+                paused = true;
+                history.first();
+            }
+            KeyCode::End => history.last(),
+            KeyCode::Char('g') => goto = Some(String::new()),
+            KeyCode::Char('+') => {
+                let sim = branch(&mut history, recorder.as_mut());
+                sim.set_ant_step(sim.ant_step() * 2.);
+            }
+            KeyCode::Char('-') => {
+                let sim = branch(&mut history, recorder.as_mut());
+                sim.set_ant_step(sim.ant_step() / 2.);
+            }
+            KeyCode::Char(']') => sleep = (sleep / 2).max(1),
+            KeyCode::Char('[') => sleep = (sleep * 2).min(10_000),
+            KeyCode::Char('r') => {
+                let ant_step = history.current().ant_step();
+                let mut sim =
+                    args.builder().seed(thread_rng().gen()).build()?;
+                sim.set_ant_step(ant_step);
+                if let Some(r) = recorder {
+                    *r = Recorder::new(sim.ants());
+                }
+                drawer = args.drawer(sim.seed()).raw(true);
+                history = History::new(sim, HISTORY_LIMIT);
+            }
+            _ => {}
+        }
     }
 
-    fn has_ants(&self) -> bool {
-        !self.ants.is_empty()
+    Ok(())
+}
+
+/// Gets the current frame for modification, the newer frames are discarded
+/// also from the recorder
+fn branch<'a>(
+    history: &'a mut History,
+    recorder: Option<&mut Recorder>,
+) -> &'a mut AntRod {
+    if let (Some(r), false) = (recorder, history.is_latest()) {
+        let sim = history.current();
+        r.rewind(sim.ants(), sim.time());
     }
+    history.current_mut()
+}
 
-    fn draw(&mut self) {
-        self.drawer
-            .draw(&self.ants, self.time as f32 * self.ant_step);
+/// Moves to the next frame in the history, new frames are simulated by
+/// `every` steps
+fn advance(
+    history: &mut History,
+    recorder: Option<&mut Recorder>,
+    every: usize,
+) {
+    if history.forward() || !history.current().has_ants() {
+        return;
     }
+    let mut sim = history.current().clone();
+    step(&mut sim, recorder, every);
+    history.push(sim);
 }
 
-#[derive(Clone)]
-struct Ant {
-    position: f32,
-    // the speed of the ant
-    speed: f32,
-    typ: AntType,
+/// Moves to the last frame at the given time, the simulation continues
+/// until the time if it is later than all the frames
+fn seek(
+    history: &mut History,
+    mut recorder: Option<&mut Recorder>,
+    every: usize,
+    time: f32,
+) {
+    history.last();
+    while history.current().time() < time && history.current().has_ants() {
+        advance(history, recorder.as_deref_mut(), every);
+    }
+    history.seek(time);
 }
 
-impl Default for Ant {
-    fn default() -> Self {
-        Self {
-            position: thread_rng().gen_range(0.0..1.),
-            speed: if thread_rng().gen_bool(0.5) { 1. } else { -1. },
-            typ: AntType::Some,
+/// Waits for key press at most `timeout` (forever if [`None`])
+fn read_key(timeout: Option<Duration>) -> Result<Option<KeyEvent>> {
+    let deadline = timeout.map(|t| Instant::now() + t);
+    loop {
+        if let Some(d) = deadline {
+            if !event::poll(d.saturating_duration_since(Instant::now()))? {
+                return Ok(None);
+            }
+        }
+        if let TermEvent::Key(k) = event::read()? {
+            if k.kind == KeyEventKind::Press {
+                return Ok(Some(k));
+            }
         }
     }
 }
 
-#[derive(Clone, PartialEq, Default, Copy)]
-enum AntType {
-    #[default]
-    None,
-    Some,
-    Molly,
+/// Keeps the terminal in raw mode while it exists
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        terminal::enable_raw_mode()
+            .wrap_err("Failed to enable raw mode of the terminal")?;
+        Ok(Self)
+    }
 }
 
-impl AntType {
-    fn set(&mut self, other: AntType) {
-        match (&self, other) {
-            (AntType::Molly, AntType::Some) => {}
-            (_, a) => *self = a,
-        }
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        _ = terminal::disable_raw_mode();
     }
 }
 
-impl ToString for AntType {
-    fn to_string(&self) -> String {
-        // white space
-        const NO_ANT: &str = "\x1b[47m ";
-        // black on white
-        const AN_ANT: &str = "\x1b[30m\x1b[47m●";
-        // magenta on white
-        const MOLLY: &str = "\x1b[35m\x1b[47m●";
-
-        let ant = match self {
-            AntType::None => NO_ANT,
-            AntType::Some => AN_ANT,
-            AntType::Molly => MOLLY,
-        };
+/// Runs many simulations for each combination of the ant counts, molly
+/// indexes and biases and prints the statistics of molly's falls as csv
+fn run_sweep(args: &Args) -> Result<()> {
+    let seed = args.seed.unwrap_or_else(|| thread_rng().gen());
+    // molly is in the center by default
+    let mollies: Vec<_> = if args.mollies.is_empty() {
+        vec![None]
+    } else {
+        args.mollies.iter().copied().map(Some).collect()
+    };
 
-        format!("{ant}\x1b[0m")
+    println!("count,molly,bias,runs,mean,median,min,max,variance,left,right");
+    for &count in &args.counts {
+        for &molly in &mollies {
+            let index = molly.unwrap_or(count / 2);
+            // molly must be one of the ants
+            if index >= count {
+                continue;
+            }
+            for &bias in &args.biases {
+                let conf = args.builder().count(count).molly(index).bias(bias);
+                let falls =
+                    batch::molly_falls(&conf, args.runs, args.threads, seed)?;
+
+                // in seconds as in the batch mode
+                let mut times: Vec<_> =
+                    falls.iter().map(|f| f.time * 100.).collect();
+                let left =
+                    falls.iter().filter(|f| f.side == Side::Left).count();
+                let stats = Stats::new(&mut times).map_or_else(
+                    || ",,,,".to_owned(),
+                    |s| {
+                        format!(
+                            "{},{},{},{},{}",
+                            s.mean, s.median, s.min, s.max, s.variance
+                        )
+                    },
+                );
+                println!(
+                    "{count},{index},{bias},{},{stats},{left},{}",
+                    falls.len(),
+                    falls.len() - left,
+                );
+            }
+        }
     }
+
+    Ok(())
 }
 
-struct Drawer {
-    ant_vec: Vec<AntType>,
-    buffer: String,
+/// Runs many simulations without drawing them and prints statistics of
+/// molly's fall
+fn run_batch(args: &Args) -> Result<()> {
+    // width of the longest bar in the histogram in characters
+    const WIDTH: usize = 40;
+
+    let seed = args.seed.unwrap_or_else(|| thread_rng().gen());
+    let falls =
+        batch::molly_falls(&args.builder(), args.runs, args.threads, seed)?;
+
+    let mut times: Vec<_> = falls.iter().map(|f| f.time * 100.).collect();
+    let left = falls.iter().filter(|f| f.side == Side::Left).count();
+    let right = falls.len() - left;
+
+    println!("runs: {}  seed: {}", falls.len(), seed);
+
+    let (square, bar) = if args.ascii {
+        ("^2", "#")
+    } else {
+        ("²", "█")
+    };
+    let Some(stats) = Stats::new(&mut times) else {
+        return Ok(());
+    };
+
+    println!(
+        "
+molly fall time:
+  mean:     {:.3}s
+  median:   {:.3}s
+  min:      {:.3}s
+  max:      {:.3}s
+  variance: {:.3}s{square}
+
+molly fall side:
+  left:  {left} ({:.1}%)
+  right: {right} ({:.1}%)
+",
+        stats.mean,
+        stats.median,
+        stats.min,
+        stats.max,
+        stats.variance,
+        left as f32 / falls.len() as f32 * 100.,
+        right as f32 / falls.len() as f32 * 100.,
+    );
+
+    let counts = stats.histogram(&times, args.bins);
+    let size = stats.bin_size(args.bins);
+    let most = counts.iter().copied().max().unwrap_or_default().max(1);
+
+    println!("histogram:");
+    for (i, c) in counts.iter().enumerate() {
+        let from = stats.min + size * i as f32;
+        println!(
+            "  {:>8.3}s - {:>8.3}s | {} {c}",
+            from,
+            from + size,
+            bar.repeat(c * WIDTH / most),
+        );
+    }
+
+    Ok(())
 }
 
-impl Drawer {
-    fn new(resolution: usize) -> Self {
-        Self {
-            ant_vec: vec![AntType::None; resolution],
-            buffer: String::new(),
+/// Prints the result computed by the solver without running the simulation
+fn solve(sim: &AntRod, args: &Args) -> Result<()> {
+    let ants = sim.ants();
+    if sim.has_walls() {
+        return Err(Report::msg(
+            "The solver supports only absorbing and periodic ends",
+        ));
+    }
+    if !sim.same_speeds() {
+        return Err(Report::msg(
+            "The solver requires all the ants to have the same speed",
+        ));
+    }
+
+    if args.ring {
+        match solver::ring_period(ants) {
+            Some(t) => println!(
+                "molly returns to her start at {:.3}s  seed: {}",
+                t * 100.,
+                sim.seed(),
+            ),
+            None => println!("there is no molly"),
         }
+        return Ok(());
     }
 
-    /// Expects that `ants` is ordered by position
-    fn draw(&mut self, ants: &Vec<Ant>, time: f32) {
-        self.ant_vec.fill(AntType::None);
+    match solver::solve(ants) {
+        Some(f) => println!(
+            "molly falls off the {} end at {:.3}s  seed: {}",
+            f.side,
+            f.time * 100.,
+            sim.seed(),
+        ),
+        None => println!("there is no molly"),
+    }
+    Ok(())
+}
 
-        // set the ants to their positions
-        for a in ants {
-            let pos = (a.position * self.ant_vec.len() as f32) as usize;
-            self.ant_vec[pos].set(a.typ)
-        }
+/// Animates the event driven simulation by sampling it every `ant_step` and
+/// prints the exact fall times when all the ants are gone
+fn run_exact(
+    mut sim: EventRod,
+    drawer: &mut Drawer,
+    mut recorder: Option<&mut Recorder>,
+    longest: Option<f32>,
+    args: &Args,
+) -> Vec<Event> {
+    let sleep = Duration::from_millis(args.sleep);
 
-        self.buffer.clear();
-        // move 2 lines up and left, clear all from cursor to the end
-        self.buffer += "\x1b[2F\x1b[0J";
-        for a in &self.ant_vec {
-            self.buffer += &a.to_string();
+    let step = args.ant_step * args.steps_per_frame() as f32;
+    let mut frame = 0;
+    let mut log = vec![];
+    drawer.draw(sim.ants(), sim.time());
+    while sim.has_ants() {
+        thread::sleep(sleep);
+        frame += 1;
+        sim.advance_to(frame as f32 * step);
+        if let Some(r) = &mut recorder {
+            r.record(sim.ants(), sim.events(), sim.time());
         }
+        log.extend_from_slice(sim.events());
+        drawer.mark(sim.events());
+        drawer.draw(sim.ants(), sim.time());
+    }
 
-        println!("{}\ntime: {:.1}s", self.buffer, time * 100.0);
+    for f in sim.fallen() {
+        print_fall(f, args.theme);
     }
+    print_summary(
+        &Summary::new(sim.fallen(), sim.collisions()),
+        longest,
+        args.theme,
+    );
+
+    log
 }
 
 // simulation parameters
 struct Args {
     ant_count: usize,
-    molly_index: usize,
+    molly_index: Option<usize>,
+    // probability that ant faces right
+    bias: f64,
+    positions: Positions,
+    // the values for the sweep, in other modes there is at most one value
+    counts: Vec<usize>,
+    mollies: Vec<usize>,
+    biases: Vec<f64>,
     ant_step: f32,
     sleep: u64,
     regular: bool,
+    exact: bool,
+    solve: bool,
+    seed: Option<u64>,
+    batch: bool,
+    sweep: bool,
+    runs: usize,
+    threads: usize,
+    bins: usize,
+    ring: bool,
+    left: Boundary,
+    right: Boundary,
+    // range of random speeds of the ants
+    speeds: Option<(f32, f32)>,
+    collision: Collision,
+    spacetime: bool,
+    // draw the ants as braille dots
+    braille: bool,
+    glyphs: Glyphs,
+    theme: Theme,
+    // use only ascii characters and don't move the cursor
+    ascii: bool,
+    // print each frame after the last one instead of moving the cursor
+    append: bool,
+    interactive: bool,
+    // print the events as json lines instead of drawing
+    events: bool,
+    // run without drawing and print only the results
+    no_draw: bool,
+    // with `no_draw` print the ants after each sample instead of the results
+    snapshots: bool,
+    format: Format,
+    // number of simulation steps per drawn line in the space-time diagram
+    sample: Option<usize>,
+    // file to which the svg is exported
+    svg: Option<String>,
+    // indexes of the tracked ants
+    track: Vec<usize>,
+    // ants loaded from scenario file
+    scenario: Option<Vec<Ant>>,
+    // file to which the run is recorded
+    record: Option<String>,
+    // recorded run that is replayed
+    replay: Option<Record>,
+    // run the two dimensional arena instead of the rod
+    arena: Option<Shape>,
+    directions: Directions,
+    // run the ants on the graph of sticks instead of the rod
+    graph: Option<Graph>,
+    junction: Junction,
     resolution: usize,
+    // number of lines of the terminal, used by the arena
+    height: usize,
     start: bool,
 }
 
@@ -263,64 +760,655 @@ impl Args {
             };
         }
 
+        let (width, height) = terminal_size::terminal_size().unwrap_or((
+            terminal_size::Width(100),
+            terminal_size::Height(100),
+        ));
+
+        let mut theme = None;
+        let mut format = None;
+        let mut show_help = false;
+
         let mut res = Args {
             ant_count: 25,
-            molly_index: usize::MAX,
+            molly_index: None,
+            bias: 0.5,
+            positions: Positions::default(),
+            counts: vec![25],
+            mollies: vec![],
+            biases: vec![0.5],
             ant_step: 0.001,
             sleep: 50,
             regular: false,
-            resolution: terminal_size::terminal_size()
-                .unwrap_or((
-                    terminal_size::Width(100),
-                    terminal_size::Height(100),
-                ))
-                .0
-                 .0
-                .into(),
+            exact: false,
+            solve: false,
+            seed: None,
+            batch: false,
+            sweep: false,
+            runs: 1000,
+            threads: 1,
+            bins: 10,
+            track: vec![],
+            scenario: None,
+            record: None,
+            replay: None,
+            svg: None,
+            ring: false,
+            left: Boundary::default(),
+            right: Boundary::default(),
+            speeds: None,
+            collision: Collision::default(),
+            spacetime: false,
+            braille: false,
+            glyphs: Glyphs::default(),
+            theme: Theme::default(),
+            ascii: false,
+            append: false,
+            interactive: false,
+            events: false,
+            no_draw: false,
+            snapshots: false,
+            format: Format::Text,
+            sample: None,
+            arena: None,
+            directions: Directions::default(),
+            graph: None,
+            junction: Junction::default(),
+            resolution: width.0.into(),
+            height: height.0.into(),
             start: true,
         };
 
         while let Some(a) = args.next() {
             match a {
-                "-c" | "--count" => res.ant_count = next!(usize, args, a),
-                "-m" | "--molly" => res.molly_index = next!(usize, args, a),
+                "-c" | "--count" => {
+                    res.counts =
+                        whole(parse_values(&next!(String, args, a))?)?;
+                    res.ant_count = res.counts[0];
+                }
+                "-m" | "--molly" => {
+                    res.mollies =
+                        whole(parse_values(&next!(String, args, a))?)?;
+                    res.molly_index = Some(res.mollies[0]);
+                }
+                "--positions" => res.positions = next!(Positions, args, a),
+                "--bias" => {
+                    res.biases = parse_values(&next!(String, args, a))?;
+                    res.bias = res.biases[0];
+                }
                 "-s" | "--speed" => res.ant_step = next!(f32, args, a),
                 "-d" | "--delta" => res.sleep = next!(u64, args, a),
                 "--regular" => res.regular = true,
+                "--ring" => {
+                    res.left = Boundary::Periodic;
+                    res.right = Boundary::Periodic;
+                }
+                "--left" => res.left = next!(Boundary, args, a),
+                "--right" => res.right = next!(Boundary, args, a),
+                "--speeds" => {
+                    res.speeds = Some(parse_range(&next!(String, args, a))?)
+                }
+                "--track" => {
+                    res.track = next!(String, args, a)
+                        .split(',')
+                        .map(|i| i.trim().parse())
+                        .collect::<Result<_, _>>()?
+                }
+                "--collision" => res.collision = next!(Collision, args, a),
+                "-t" | "--spacetime" => res.spacetime = true,
+                "--braille" => res.braille = true,
+                "--glyphs" => res.glyphs = next!(Glyphs, args, a),
+                "--theme" => theme = Some(next!(Theme, args, a)),
+                "--ascii" => res.ascii = true,
+                "-i" | "--interactive" => res.interactive = true,
+                "--events" => res.events = true,
+                "--no-draw" => res.no_draw = true,
+                "--snapshots" => res.snapshots = true,
+                "--format" => format = Some(next!(Format, args, a)),
+                "--svg" => res.svg = Some(next!(String, args, a)),
+                "--sample" => res.sample = Some(next!(usize, args, a)),
+                "-e" | "--exact" => res.exact = true,
+                "--solve" => res.solve = true,
+                "--seed" => res.seed = Some(next!(u64, args, a)),
+                "batch" => res.batch = true,
+                "sweep" => res.sweep = true,
+                "-n" | "--runs" => res.runs = next!(usize, args, a),
+                "-j" | "--threads" => res.threads = next!(usize, args, a),
+                "--bins" => res.bins = next!(usize, args, a),
+                "--scenario" => {
+                    let ants = scenario::load(&next!(String, args, a))?;
+                    res.ant_count = ants.len();
+                    res.scenario = Some(ants);
+                }
+                "--record" => res.record = Some(next!(String, args, a)),
+                "--replay" => {
+                    res.replay = Some(Record::load(&next!(String, args, a))?)
+                }
+                "--arena" => res.arena = Some(next!(Shape, args, a)),
+                "--directions" => res.directions = next!(Directions, args, a),
+                "--graph" => res.graph = Some(next!(Graph, args, a)),
+                "--junction" => res.junction = next!(Junction, args, a),
                 "-r" | "--resolution" => {
                     res.resolution = next!(usize, args, a)
                 }
-                "-h" | "-?" | "-help" | "--help" => {
-                    help();
-                    res.start = false;
-                },
+                "-h" | "-?" | "-help" | "--help" => show_help = true,
                 _ => return Err(Report::msg(format!("invalid argument {a}"))),
             }
         }
 
-        if res.molly_index == usize::MAX {
-            res.molly_index = res.ant_count / 2;
+        if res.ascii
+            && (res.braille
+                || res.glyphs == Glyphs::Triangle
+                || theme.is_some_and(|t| t != Theme::Plain))
+        {
+            return Err(Report::msg(
+                "The ascii mode cannot be combined with braille, glyphs or \
+                colors",
+            ));
+        }
+
+        // without colors when they are not wanted or not supported
+        res.theme = theme.unwrap_or_else(Theme::detect);
+        if res.ascii {
+            res.theme = Theme::Plain;
+            res.glyphs = Glyphs::Ascii;
+        }
+        // the cursor can be moved only in terminal
+        res.append = res.ascii || !io::stdout().is_terminal();
+
+        if show_help {
+            help(res.theme != Theme::Plain, res.ascii);
+            res.start = false;
+            return Ok(res);
+        }
+
+        if res.replay.is_some()
+            && (res.batch || res.record.is_some() || res.scenario.is_some())
+        {
+            return Err(Report::msg(
+                "The replay cannot be combined with batch, record or scenario",
+            ));
+        }
+
+        if res.record.is_some() && (res.batch || res.interactive) {
+            return Err(Report::msg(
+                "The record is not supported in batch and interactive mode",
+            ));
         }
 
-        if res.molly_index >= res.ant_count {
-            return Err(Report::msg(format!(
-                "Invalid molly index {} out of {}",
-                res.molly_index, res.ant_count
-            )));
+        // the replay repeats the recorded run
+        if let Some(r) = &res.replay {
+            res.seed = Some(r.seed);
+            res.ant_step = r.step;
+            res.left = r.left;
+            res.right = r.right;
+            res.collision = r.collision;
+            res.exact = r.exact;
+            res.ant_count = r.ants.len();
+            res.scenario = Some(r.ants.clone());
+        }
+
+        if !res.sweep
+            && (res.counts.len() > 1
+                || res.mollies.len() > 1
+                || res.biases.len() > 1)
+        {
+            return Err(Report::msg(
+                "The ranges of values are supported only in sweep mode",
+            ));
+        }
+
+        if res.sweep
+            && (res.batch || res.scenario.is_some() || res.replay.is_some())
+        {
+            return Err(Report::msg(
+                "The sweep cannot be combined with batch, scenario or replay",
+            ));
+        }
+
+        res.ring = res.left == Boundary::Periodic;
+        // the ants can never fall
+        let closed = res.left != Boundary::Absorbing
+            && res.right != Boundary::Absorbing;
+
+        if closed && (res.batch || res.sweep) {
+            return Err(Report::msg(
+                "The batch and sweep mode need at least one absorbing end",
+            ));
+        }
+
+        if res.exact
+            && (res.left != Boundary::Absorbing
+                || res.right != Boundary::Absorbing)
+        {
+            return Err(Report::msg(
+                "The exact mode supports only absorbing ends of the rod",
+            ));
+        }
+
+        if res.no_draw && (res.interactive || res.events) {
+            return Err(Report::msg(
+                "The no draw mode cannot be combined with interactive mode or \
+                events",
+            ));
+        }
+
+        if format.is_some() && !res.no_draw {
+            return Err(Report::msg(
+                "The format can be chosen only in the no draw mode",
+            ));
+        }
+        res.format = format.unwrap_or(Format::Text);
+
+        if res.snapshots && (!res.no_draw || res.format == Format::Text) {
+            return Err(Report::msg(
+                "The snapshots are printed only in no draw mode with json or \
+                csv format",
+            ));
+        }
+
+        if res.no_draw && closed && !res.snapshots {
+            return Err(Report::msg(
+                "The ants never fall, so they can run without drawing only \
+                with snapshots",
+            ));
+        }
+
+        if res.events && res.interactive {
+            return Err(Report::msg(
+                "The events cannot be printed in interactive mode",
+            ));
+        }
+
+        if res.interactive && res.exact {
+            return Err(Report::msg(
+                "The interactive mode is not supported in exact mode",
+            ));
+        }
+
+        let rod_only = res.batch
+            || res.sweep
+            || res.exact
+            || res.solve
+            || res.interactive
+            || res.events
+            || res.no_draw
+            || res.spacetime
+            || res.braille
+            || (res.glyphs != Glyphs::default() && !res.ascii)
+            || res.regular
+            || res.bias != 0.5
+            || res.positions != Positions::default()
+            || res.left != Boundary::Absorbing
+            || res.right != Boundary::Absorbing
+            || !res.track.is_empty()
+            || res.scenario.is_some()
+            || res.record.is_some()
+            || res.replay.is_some()
+            || res.svg.is_some();
+        if res.arena.is_some()
+            && (rod_only
+                || res.graph.is_some()
+                || res.collision != Collision::default())
+        {
+            return Err(Report::msg(
+                "The arena supports only the count, molly, directions, speed, \
+                speeds, delta, seed, resolution, theme and ascii",
+            ));
+        }
+
+        if res.graph.is_some() && rod_only {
+            return Err(Report::msg(
+                "The graph supports only the count, molly, junction, speed, \
+                speeds, collision, delta, seed, resolution, theme and ascii",
+            ));
+        }
+
+        if res.threads == 0 || res.bins == 0 {
+            return Err(Report::msg(
+                "The number of threads and bins must be at least 1",
+            ));
+        }
+
+        if res.sample == Some(0) {
+            return Err(Report::msg("The sample must be at least 1"));
         }
 
         Ok(res)
     }
+
+    /// Creates drawer for simulation with the given seed
+    fn drawer(&self, seed: u64) -> Drawer {
+        Drawer::new(self.resolution, seed)
+            .ring(self.ring)
+            .spacetime(self.spacetime)
+            .braille(self.braille)
+            .glyphs(self.glyphs)
+            .theme(self.theme)
+            .ascii(self.ascii)
+            .append(self.append)
+    }
+
+    /// Creates the configuration of the simulation
+    fn builder(&self) -> AntRodBuilder {
+        let mut res = AntRod::builder()
+            .count(self.ant_count)
+            .step(self.ant_step)
+            .regular(self.regular)
+            .bias(self.bias)
+            .positions(self.positions)
+            .left(self.left)
+            .right(self.right)
+            .collision(self.collision);
+        if let Some(m) = self.molly_index {
+            res = res.molly(m);
+        }
+        if let Some(s) = self.seed {
+            res = res.seed(s);
+        }
+        if let Some((min, max)) = self.speeds {
+            res = res.speeds(min, max);
+        }
+        if let Some(a) = &self.scenario {
+            res = res.ants(a.clone());
+        }
+        res.track(self.track.clone())
+    }
+
+    /// Creates the configuration of the arena with the given shape
+    fn arena_builder(&self, shape: Shape) -> ArenaBuilder {
+        let mut res = Arena::builder()
+            .count(self.ant_count)
+            .step(self.ant_step)
+            .shape(shape)
+            .directions(self.directions);
+        if let Some(m) = self.molly_index {
+            res = res.molly(m);
+        }
+        if let Some(s) = self.seed {
+            res = res.seed(s);
+        }
+        if let Some((min, max)) = self.speeds {
+            res = res.speeds(min, max);
+        }
+        res
+    }
+
+    /// Creates the configuration of the simulation on the graph
+    fn network_builder(&self, graph: &Graph) -> NetworkBuilder {
+        let mut res = Network::builder(graph.clone())
+            .count(self.ant_count)
+            .step(self.ant_step)
+            .junction(self.junction)
+            .collision(self.collision);
+        if let Some(m) = self.molly_index {
+            res = res.molly(m);
+        }
+        if let Some(s) = self.seed {
+            res = res.seed(s);
+        }
+        if let Some((min, max)) = self.speeds {
+            res = res.speeds(min, max);
+        }
+        res
+    }
+
+    /// Gets the number of simulation steps between two drawn frames. In the
+    /// space-time diagram it is by default chosen so that the ants move by
+    /// about one character per line.
+    fn steps_per_frame(&self) -> usize {
+        if !self.spacetime {
+            return 1;
+        }
+        self.sample.unwrap_or_else(|| {
+            let cell = 1. / self.resolution as f32;
+            ((cell / self.ant_step).round() as usize).max(1)
+        })
+    }
+}
+
+/// Runs the two dimensional arena drawn over the whole terminal until all
+/// the ants fall
+fn run_arena(mut arena: Arena, args: &Args) -> Result<()> {
+    let sleep = Duration::from_millis(args.sleep);
+    let mut drawer = ArenaDrawer::new(
+        args.resolution,
+        args.height,
+        arena.seed(),
+        arena.shape(),
+    )
+    .theme(args.theme)
+    .ascii(args.ascii)
+    .append(args.append);
+    drawer.draw(arena.ants(), arena.time(), arena.collisions());
+    while arena.has_ants() {
+        thread::sleep(sleep);
+        arena.step();
+        drawer.draw(arena.ants(), arena.time(), arena.collisions());
+    }
+    println!();
+
+    if let Some(e) = arena.molly_exit() {
+        println!(
+            "{}molly{} fell at ({:.3}, {:.3}) at {:.1}s",
+            args.theme.color(AntType::Molly),
+            args.theme.reset(),
+            e.x,
+            e.y,
+            e.time * 100.
+        );
+    }
+    println!(
+        "collisions: {}  last fall: {:.1}s",
+        arena.collisions(),
+        arena.exits().last().map_or(0., |e| e.time) * 100.
+    );
+    Ok(())
+}
+
+/// Runs the ants on the graph of sticks until all the ants fall
+fn run_network(mut sim: Network, args: &Args) -> Result<()> {
+    let sleep = Duration::from_millis(args.sleep);
+    let mut drawer = NetworkDrawer::new(args.resolution, sim.seed())
+        .theme(args.theme)
+        .ascii(args.ascii)
+        .append(args.append);
+    drawer.draw(sim.graph(), sim.ants(), sim.time());
+    while sim.has_ants() {
+        thread::sleep(sleep);
+        sim.step();
+        drawer.draw(sim.graph(), sim.ants(), sim.time());
+    }
+
+    if let Some(f) = sim.molly_fall() {
+        println!(
+            "{}molly{} fell at leaf {} at {:.1}s",
+            args.theme.color(AntType::Molly),
+            args.theme.reset(),
+            f.leaf,
+            f.time * 100.
+        );
+    }
+    println!(
+        "collisions: {}  last fall: {:.1}s",
+        sim.collisions(),
+        sim.fallen().last().map_or(0., |f| f.time) * 100.
+    );
+    Ok(())
+}
+
+/// Prints when and where the ant fell, molly and tracked ants are colored
+fn print_fall(f: &Fall, theme: Theme) {
+    let note = match f.typ {
+        AntType::Molly => " (molly)",
+        AntType::Tracked(_) => " (tracked)",
+        _ => "",
+    };
+    let (color, reset) = match f.typ {
+        AntType::Molly | AntType::Tracked(_) => {
+            (theme.color(f.typ), theme.reset())
+        }
+        _ => ("", ""),
+    };
+    println!(
+        "{color}ant {:>3}{note}: fell {} at {:.3}s{reset}",
+        f.id,
+        f.side,
+        f.time * 100.,
+    );
+}
+
+/// Prints the summary of finished run, `longest` is the longest time that
+/// any ant can stay on the rod if it is known
+fn print_summary(summary: &Summary, longest: Option<f32>, theme: Theme) {
+    println!();
+    match summary.molly {
+        Some(m) => println!(
+            "{}molly fell {} at {:.3}s{}",
+            theme.color(AntType::Molly),
+            m.side,
+            m.time * 100.,
+            theme.reset(),
+        ),
+        None => println!("there is no molly"),
+    }
+    println!("collisions: {}", summary.collisions);
+    println!("fell left: {}  right: {}", summary.left, summary.right);
+    match (summary.last, longest) {
+        (Some(last), Some(longest)) => println!(
+            "last ant fell at {:.3}s, that is {:.1}% of the longest \
+            possible time {:.3}s",
+            last * 100.,
+            last / longest * 100.,
+            longest * 100.,
+        ),
+        (Some(last), None) => println!("last ant fell at {:.3}s", last * 100.),
+        _ => {}
+    }
+}
+
+/// Format of the output in the no draw mode
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(Report::msg(format!("invalid format {s}"))),
+        }
+    }
+}
+
+/// Parses single number or range `<from>..<to>[:<step>]` that includes both
+/// ends (the default step is 1), the range may have at most 10000 values
+fn parse_values(s: &str) -> Result<Vec<f64>> {
+    const MAX_VALUES: f64 = 10_000.;
+
+    let Some((from, rest)) = s.split_once("..") else {
+        return Ok(vec![s.parse()?]);
+    };
+    let (to, step) = rest.split_once(':').unwrap_or((rest, "1"));
+    let (from, to, step): (f64, f64, f64) =
+        (from.parse()?, to.parse()?, step.parse()?);
+    if !(step > 0. && from <= to && to.is_finite()) {
+        return Err(Report::msg(format!(
+            "Invalid range {s}, expected <from>..<to>[:<step>]"
+        )));
+    }
+
+    // the tolerance and rounding hide the rounding errors of the steps
+    let n = ((to - from) / step + 1e-9).floor();
+    if n >= MAX_VALUES {
+        return Err(Report::msg(format!(
+            "Invalid range {s}, it has more than {MAX_VALUES} values"
+        )));
+    }
+    let n = n as usize;
+    Ok((0..=n)
+        .map(|i| ((from + step * i as f64) * 1e9).round() / 1e9)
+        .collect())
 }
 
-fn help() {
+/// Checks that the values are whole numbers and converts them
+fn whole(values: Vec<f64>) -> Result<Vec<usize>> {
+    values
+        .into_iter()
+        .map(|v| {
+            if v >= 0. && v.fract() == 0. {
+                Ok(v as usize)
+            } else {
+                Err(Report::msg(format!("{v} is not whole number")))
+            }
+        })
+        .collect()
+}
+
+/// Parses range in the format `<min>..<max>`
+fn parse_range(s: &str) -> Result<(f32, f32)> {
+    let Some((min, max)) = s.split_once("..") else {
+        return Err(Report::msg(format!(
+            "Invalid range {s}, expected <min>..<max>"
+        )));
+    };
+    Ok((min.parse()?, max.parse()?))
+}
+
+/// Prints the help, `color` enables the colors
+fn help(color: bool, ascii: bool) {
+    // BonnyAD9 gradient
+    let mut signature = concat!(
+        "\x1b[38;2;250;50;170mB\x1b[38;2;240;50;180mo\x1b[38;2;230;50;190mn",
+        "\x1b[38;2;220;50;200mn\x1b[38;2;210;50;210my\x1b[38;2;200;50;220mA",
+        "\x1b[38;2;190;50;230mD\x1b[38;2;180;50;240m9\x1b[0m",
+    );
+    let mut g = "\x1b[92m"; // green
+    let mut i = "\x1b[23m"; // italic
+    let mut r = "\x1b[0m"; // reset
+    let mut w = "\x1b[97m"; // white
+    let mut d = "\x1b[90m"; // dark gray
+    let mut y = "\x1b[93m"; // yellow
+    if !color {
+        signature = "BonnyAD9";
+        (g, i, r, w, d, y) = ("", "", "", "", "", "");
+    }
+    // the arrow keys and the glyphs
+    let (next, back) = if ascii {
+        ("  ", "  ")
+    } else {
+        (" →", " ←")
+    };
+    let (dot, triangle, star) = if ascii {
+        ("o", "< >", "*")
+    } else {
+        ("●", "◀ ▶", "✱")
+    };
+
     println!(
-        "Welcome in {g}{i}stick_ants{r} by {}{}{}
+        "Welcome in {g}{i}stick_ants{r} by {signature}
 
 {g}Usage:{r}
   {w}stick_ants{r} {d}[<flags>]{r}
     runs the simulation
 
+  {w}stick_ants batch{r} {d}[<flags>]{r}
+    runs many simulations without drawing them and shows statistics of
+    molly's fall time and side
+
+  {w}stick_ants sweep{r} {d}[<flags>]{r}
+    runs many simulations for each combination of the values of {y}-c{r}, {y}-m{r}
+    and {y}--bias{r} which can be given as ranges {w}<from>..<to>[:<step>]{r},
+    prints csv table with the statistics of molly's fall time (in seconds
+    as in the batch mode) and side
+
 {g}Flags:{r}
   {y}-h  -?  -help  --help{r}
     shows this help
@@ -331,6 +1419,10 @@ fn help() {
   {y}-m  --molly {w}<molly index>{r}
     creates new template from the directory with the name (center is default)
 
+  {y}--track{r} {w}<index>,<index>,...{r}
+    tracks the ants with the given indexes, each is drawn with its own color
+    and its fall is shown at the end
+
   {y}-s --speed{r} {w}<speed>{r}
     how fast the simulation runs (default is 0.001)
 
@@ -340,18 +1432,189 @@ fn help() {
   {y}--regular{r}
     enables special case
 
+  {y}--bias{r} {w}<probability>{r}
+    probability that ant faces right (default is 0.5)
+
+  {y}--positions{r} {w}<distribution>[:<parameter>]{r}
+    distribution of the random positions of the ants:
+      {w}uniform{r}          anywhere on the rod (default)
+      {w}clustered:3{r}      around the given number of random centers
+      {w}gaussian:0.15{r}    around the center with the given deviation
+      {w}even:0.5{r}         evenly spaced, moved randomly by at most the
+                       given part of the space between the ants
+      {w}halves:0.2{r}       in the two halves separated by the given gap
+
+  {y}--ring{r}
+    connects the ends of the rod so that the ants never fall, with {y}--solve{r}
+    computes when molly returns to her start
+
+  {y}--left{r} {w}<boundary>{r}
+  {y}--right{r} {w}<boundary>{r}
+    what happens when an ant reaches the left/right end of the rod:
+      {w}absorbing{r}        the ant falls (default)
+      {w}reflecting{r}       the ant turns around
+      {w}sticky:<time>{r}    the ant waits and then turns around, the time is
+                       in the time an ant needs to walk the whole rod
+      {w}periodic{r}         the ant continues from the other end, it must
+                       be on both ends (the same as {y}--ring{r})
+    with reflecting or sticky end the ants with different speeds must use
+    {y}--collision exchange{r}, otherwise they could be trapped forever
+
+  {y}--speeds{r} {w}<min>..<max>{r}
+    gives the ants random speeds in the range (by default all the ants have
+    speed 1)
+
+  {y}--collision{r} {w}reverse|exchange{r}
+    what happens when two ants with different speeds meet, either they both
+    turn around (default) or they exchange their velocities
+
+  {y}-i  --interactive{r}
+    controls the simulation with keyboard:
+      {w}space{r}  pause/resume
+      {w}.{next}{r}    single step
+      {w},{back}{r}    step back in the history
+      {w}home end{r} go to the start/end of the history
+      {w}g{r}      go to time typed in seconds, confirm with enter
+      {w}+ -{r}    make the steps of the ants longer/shorter
+      {w}] [{r}    make the delay between frames shorter/longer
+      {w}r{r}      restart with new layout
+      {w}q{r}      quit
+
+  {y}-t  --spacetime{r}
+    prints each frame on new line instead of overwriting the last one, so
+    the output is space-time diagram with the world lines of the ants
+
+  {y}--braille{r}
+    draws the ants as braille dots, so that the ants close to each other and
+    small movements are visible, each character has two columns of dots and
+    each dot is one ant, below the rod is shown how many ants are in each
+    character where there are more of them
+
+  {y}--glyphs{r} {w}dot|triangle|ascii{r}
+    the characters used to draw the ants, {w}{dot}{r} for all the ants (default),
+    {w}{triangle}{r} or {w}< >{r} by the direction of the ant, the collisions are marked
+    with {w}{star}{r} (or {w}*{r} with ascii)
+
+  {y}--theme{r} {w}<theme>{r}
+    the colors used to draw the ants (the default is light, or plain when
+    the {w}NO_COLOR{r} variable is set or the output isn't terminal):
+      {w}light{r}            dark ants on white rod
+      {w}dark{r}             bright ants on black rod
+      {w}high-contrast{r}    bold ants on bright white rod
+      {w}colorblind{r}       colors distinguishable with color blindness
+      {w}plain{r}            no colors, the empty rod is drawn with dots
+
+  {y}--ascii{r}
+    uses only ascii characters without colors and prints each frame after
+    the last one instead of overwriting it, for logs and dumb terminals
+
+  {y}--sample{r} {w}<steps>{r}
+    number of simulation steps between two lines of the space-time diagram
+    (by default the ants move by about one character per line) or between
+    two snapshots (default is 1)
+
+  {y}--events{r}
+    runs without drawing and prints each collision, fall and turn of molly
+    as json line, for example:
+      {d}{{\"event\":\"fall\",\"id\":3,\"type\":\"ant\",\"side\":\"left\",\"time\":0.412}}{r}
+
+  {y}--no-draw{r}
+    runs as fast as possible without drawing and prints only the results
+
+  {y}--format{r} {w}text|json|csv{r}
+    format of the results in the no draw mode, json is single object with
+    the summary and all the falls, csv is table of the falls (default is
+    text)
+
+  {y}--snapshots{r}
+    in the no draw mode prints the positions of the ants after each sample
+    (see {y}--sample{r}) instead of the results, as json lines or csv table
+
+  {y}--svg{r} {w}<file>{r}
+    records the trajectories of all the ants and when the simulation ends
+    exports them as space-time diagram to the svg file
+
+  {y}-e  --exact{r}
+    uses the event driven simulation that computes the collisions exactly
+    and prints the exact fall time of each ant at the end
+
+  {y}--solve{r}
+    computes when and where molly falls without running the simulation
+
+  {y}--seed{r} {w}<seed>{r}
+    seed for the random layout of the ants, the same seed always gives the
+    same layout (random by default, it is shown next to the time)
+
+  {y}-n  --runs{r} {w}<runs>{r}
+    number of simulations for each point in the batch and sweep mode
+    (default is 1000)
+
+  {y}-j  --threads{r} {w}<threads>{r}
+    number of threads used in the batch and sweep mode (default is 1)
+
+  {y}--bins{r} {w}<bins>{r}
+    number of bins in the histogram of the batch mode (default is 10)
+
+  {y}--scenario{r} {w}<file>{r}
+    loads the exact initial layout of the ants from toml file, each ant is
+    given by its own table:
+      {d}[[ant]]
+      position = 0.25     # in the range [0, 1)
+      direction = \"right\" # \"left\" or \"right\"
+      speed = 1.0         # optional, default is 1
+      type = \"molly\"      # optional, \"ant\", \"molly\" or \"tracked\"{r}
+
+  {y}--record{r} {w}<file>{r}
+    saves the parameters, the initial layout and all the events (collisions,
+    falls and turns of molly) of the run to toml file
+
+  {y}--replay{r} {w}<file>{r}
+    repeats run saved with {y}--record{r} and checks that all the events
+    happen the same way, the speed of the replay can be changed with {y}-d{r}
+
+  {y}--arena{r} {w}square|disc{r}
+    runs the ants in two dimensional arena drawn over the whole terminal
+    instead of on the rod, the ants that meet head-on turn around and the
+    ants that walk over the edge fall
+
+  {y}--directions{r} {w}cardinal|any{r}
+    in which directions the ants in the arena walk, either only up, down,
+    left and right (default) or in any direction
+
+  {y}--graph{r} {w}<graph>{r}
+    runs the ants on graph of connected sticks instead of the rod, each
+    stick is drawn on its own line and the ants fall at the leaves:
+      {w}y{r}                three sticks connected at one junction
+      {w}star:<n>{r}         n sticks connected at one junction
+      {w}tree:<depth>{r}     binary tree with the given depth
+      {w}0-1,1-2,1-3{r}      the sticks given by their nodes
+
+  {y}--junction{r} {w}random|next{r}
+    how the ants on the graph choose the stick at junction, either randomly
+    (default) or the next stick at the junction after the one they came
+    from (in the order in which the sticks are given)
+
   {y}-r --resolution{r}
     how many characters should be used for the simulation
-",
-        // BonnyAD9 gradient in 3 strings
-        "\x1b[38;2;250;50;170mB\x1b[38;2;240;50;180mo\x1b[38;2;230;50;190mn",
-        "\x1b[38;2;220;50;200mn\x1b[38;2;210;50;210my\x1b[38;2;200;50;220mA",
-        "\x1b[38;2;190;50;230mD\x1b[38;2;180;50;240m9\x1b[0m",
-        g = "\x1b[92m", // green
-        i = "\x1b[23m", // italic
-        r = "\x1b[0m",  // reset
-        w = "\x1b[97m", // white
-        d = "\x1b[90m", // dark gray
-        y = "\x1b[93m"  // yellow
+"
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_of_values() {
+        assert_eq!(parse_values("3").unwrap(), [3.]);
+        assert_eq!(parse_values("1..3").unwrap(), [1., 2., 3.]);
+        // the steps don't accumulate rounding errors
+        assert_eq!(parse_values("0..0.3:0.1").unwrap(), [0., 0.1, 0.2, 0.3]);
+        assert!(parse_values("3..1").is_err());
+        assert!(parse_values("0..1e12").is_err());
+
+        assert_eq!(whole(vec![0., 5.]).unwrap(), [0, 5]);
+        assert!(whole(vec![1.5]).is_err());
+        assert!(whole(vec![-1.]).is_err());
+    }
+}
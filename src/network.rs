@@ -0,0 +1,625 @@
+//! Ants on a graph of connected sticks. The ants walk on the sticks as on
+//! the rod, at junctions they choose the next stick and they fall at the
+//! leaves of the graph (the nodes with only one stick).
+
+use std::str::FromStr;
+
+use eyre::{Report, Result};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    builder::{common_setters, Common},
+    event::EPSILON,
+    AntType, Collision,
+};
+
+/// Stick between two nodes of the graph, it has length 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stick {
+    /// The node at position 0
+    pub from: usize,
+    /// The node at position 1
+    pub to: usize,
+}
+
+/// The connected sticks
+#[derive(Clone, Debug, PartialEq)]
+pub struct Graph {
+    sticks: Vec<Stick>,
+    // the sticks connected to each node
+    nodes: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    /// The maximum depth of binary tree
+    pub const MAX_DEPTH: u32 = 16;
+
+    /// Creates graph from the sticks, the nodes are numbered from 0
+    pub fn new(sticks: Vec<Stick>) -> Result<Self> {
+        if sticks.is_empty() {
+            return Err(Report::msg("The graph must have at least one stick"));
+        }
+
+        // connected graph has at most one more node than sticks, check it
+        // before allocating the nodes
+        let last = sticks.iter().map(|s| s.from.max(s.to)).max();
+        let last = last.unwrap_or_default();
+        if last > sticks.len() {
+            return Err(Report::msg(format!(
+                "Invalid node {last}, the connected graph with {n} sticks \
+                has only the nodes 0 to {n}",
+                n = sticks.len()
+            )));
+        }
+
+        let mut nodes = vec![vec![]; last + 1];
+        for (i, s) in sticks.iter().enumerate() {
+            if s.from == s.to {
+                return Err(Report::msg(format!(
+                    "The stick {}-{} connects node to itself",
+                    s.from, s.to
+                )));
+            }
+            nodes[s.from].push(i);
+            nodes[s.to].push(i);
+        }
+
+        if let Some(n) = nodes.iter().position(|n| n.is_empty()) {
+            return Err(Report::msg(format!("The node {n} has no sticks")));
+        }
+
+        let res = Self { sticks, nodes };
+        if !res.is_connected() {
+            return Err(Report::msg("The graph must be connected"));
+        }
+        if res.leaves().next().is_none() {
+            return Err(Report::msg(
+                "The graph must have at least one leaf where the ants fall",
+            ));
+        }
+        Ok(res)
+    }
+
+    /// Creates `n` sticks connected at one node, the node 0 is the center
+    pub fn star(n: usize) -> Result<Self> {
+        Self::new((1..=n).map(|to| Stick { from: 0, to }).collect())
+    }
+
+    /// Creates binary tree with the given depth (at most
+    /// [`Graph::MAX_DEPTH`]), the node 0 is the root
+    pub fn tree(depth: u32) -> Result<Self> {
+        let nodes = (depth <= Self::MAX_DEPTH)
+            .then(|| 2usize.checked_pow(depth + 1))
+            .flatten()
+            .ok_or_else(|| {
+                Report::msg(format!(
+                    "Invalid tree depth {depth}, the maximum is {}",
+                    Self::MAX_DEPTH
+                ))
+            })?
+            - 1;
+        Self::new(
+            (1..nodes)
+                .map(|to| Stick {
+                    from: (to - 1) / 2,
+                    to,
+                })
+                .collect(),
+        )
+    }
+
+    /// Gets all the sticks
+    pub fn sticks(&self) -> &[Stick] {
+        &self.sticks
+    }
+
+    /// Gets the sticks connected to the node
+    pub fn node(&self, node: usize) -> &[usize] {
+        &self.nodes[node]
+    }
+
+    /// Checks whether the node is a leaf where the ants fall
+    pub fn is_leaf(&self, node: usize) -> bool {
+        self.nodes[node].len() == 1
+    }
+
+    /// Gets all the leaves
+    pub fn leaves(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.nodes.len()).filter(|n| self.is_leaf(*n))
+    }
+
+    fn is_connected(&self) -> bool {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack = vec![0];
+        seen[0] = true;
+        while let Some(n) = stack.pop() {
+            for s in &self.nodes[n] {
+                let s = self.sticks[*s];
+                let other = if s.from == n { s.to } else { s.from };
+                if !seen[other] {
+                    seen[other] = true;
+                    stack.push(other);
+                }
+            }
+        }
+        seen.iter().all(|s| *s)
+    }
+}
+
+impl FromStr for Graph {
+    type Err = Report;
+
+    /// Parses `y`, `star:<n>`, `tree:<depth>` or list of sticks, e.g.
+    /// `0-1,1-2,1-3`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            _ if s == "y" => Graph::star(3),
+            Some(("star", n)) => Graph::star(n.parse()?),
+            Some(("tree", d)) => Graph::tree(d.parse()?),
+            _ => Graph::new(
+                s.split(',')
+                    .map(|s| {
+                        let (from, to) =
+                            s.split_once('-').ok_or_else(|| {
+                                Report::msg(format!("invalid stick {s}"))
+                            })?;
+                        Ok(Stick {
+                            from: from.trim().parse()?,
+                            to: to.trim().parse()?,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+        }
+    }
+}
+
+/// How the ants choose the next stick at a junction
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Junction {
+    /// Any of the other sticks with the same probability
+    #[default]
+    Random,
+    /// The next stick at the node after the one the ant came from
+    Next,
+}
+
+impl FromStr for Junction {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Junction::Random),
+            "next" => Ok(Junction::Next),
+            _ => Err(Report::msg(format!("invalid junction rule {s}"))),
+        }
+    }
+}
+
+/// Ant on the graph
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetAnt {
+    /// Index of the stick on which the ant is
+    pub stick: usize,
+    /// Position on the stick in the range [0, 1)
+    pub position: f32,
+    /// The speed of the ant, negative if it walks to the `from` node
+    pub speed: f32,
+    pub typ: AntType,
+    /// Identifies the ant, stays the same even after collisions
+    pub id: usize,
+}
+
+/// Records when and at which leaf an ant fell
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NetFall {
+    pub id: usize,
+    pub typ: AntType,
+    pub leaf: usize,
+    pub time: f32,
+}
+
+/// The simulation of ants on the graph, the ants move by a fixed step
+#[derive(Clone)]
+pub struct Network {
+    graph: Graph,
+    // the vector is always ordered by stick and position on it
+    ants: Vec<NetAnt>,
+    ant_step: f32,
+    // number of steps
+    time: usize,
+    junction: Junction,
+    collision: Collision,
+    fallen: Vec<NetFall>,
+    collisions: usize,
+    seed: u64,
+    // chooses the sticks at junctions
+    rng: StdRng,
+}
+
+/// Configures and creates [`Network`], the seed is used also for the random
+/// choices at the junctions
+#[derive(Clone, Debug)]
+pub struct NetworkBuilder {
+    graph: Graph,
+    common: Common,
+    junction: Junction,
+    collision: Collision,
+    ants: Option<Vec<NetAnt>>,
+}
+
+impl Network {
+    /// Creates builder for simulation on the graph with the default
+    /// parameters
+    pub fn builder(graph: Graph) -> NetworkBuilder {
+        NetworkBuilder {
+            graph,
+            common: Common::default(),
+            junction: Junction::default(),
+            collision: Collision::default(),
+            ants: None,
+        }
+    }
+
+    /// Moves all the ants by one step, the collisions and the crossings of
+    /// the nodes are resolved in the order in which they happen
+    pub fn step(&mut self) {
+        let start = self.time();
+        let mut left = self.ant_step;
+        while let Some(dt) = self.next_event().filter(|dt| *dt <= left) {
+            self.move_by(dt);
+            left -= dt;
+            self.resolve(start + self.ant_step - left);
+        }
+        self.move_by(left);
+        self.time += 1;
+    }
+
+    /// Gets the time remaining to the next collision or arrival at a node
+    fn next_event(&self) -> Option<f32> {
+        let mut dt = f32::INFINITY;
+        for a in &self.ants {
+            let dist = if a.speed > 0. {
+                1. - a.position
+            } else {
+                a.position
+            };
+            dt = dt.min(dist / a.speed.abs());
+        }
+        // the ants meet also when the faster one catches up the slower one
+        for (i, j) in self.neighbours() {
+            let (a, b) = (&self.ants[i], &self.ants[j]);
+            if a.speed > b.speed {
+                dt = dt.min((b.position - a.position) / (a.speed - b.speed));
+            }
+        }
+        dt.is_finite().then_some(dt.max(0.))
+    }
+
+    fn move_by(&mut self, dt: f32) {
+        for a in &mut self.ants {
+            a.position += a.speed * dt;
+        }
+    }
+
+    /// Gets the indexes of the ants that are next to each other on the same
+    /// stick, the first one is closer to the `from` node
+    fn neighbours(&self) -> Vec<(usize, usize)> {
+        (1..self.ants.len())
+            .filter(|i| self.ants[i - 1].stick == self.ants[*i].stick)
+            .map(|i| (i - 1, i))
+            .collect()
+    }
+
+    /// Resolves the collisions of the ants that touch, removes the ants that
+    /// fell at the leaves and moves one ant over its junction
+    fn resolve(&mut self, time: f32) {
+        for (i, j) in self.neighbours() {
+            let (a, b) = (&self.ants[i], &self.ants[j]);
+            if b.position - a.position <= EPSILON && a.speed > b.speed {
+                let (va, vb) = self.collision.speeds(a.speed, b.speed);
+                self.ants[i].speed = va;
+                self.ants[j].speed = vb;
+                self.collisions += 1;
+            }
+        }
+
+        let graph = &self.graph;
+        let fallen = &mut self.fallen;
+        self.ants.retain(|a| {
+            let Some(leaf) = at_node(graph, a).filter(|n| graph.is_leaf(*n))
+            else {
+                return true;
+            };
+            fallen.push(NetFall {
+                id: a.id,
+                typ: a.typ,
+                leaf,
+                time,
+            });
+            false
+        });
+
+        // only one ant crosses at once, so that it meets the ants that are
+        // at the junction on the next stick
+        let Some((i, node)) = self
+            .ants
+            .iter()
+            .enumerate()
+            .find_map(|(i, a)| Some((i, at_node(&self.graph, a)?)))
+        else {
+            return;
+        };
+
+        let a = &self.ants[i];
+        let sticks = self.graph.node(node);
+        let from = sticks.iter().position(|s| *s == a.stick);
+        let from = from.unwrap_or_default();
+        let next = match self.junction {
+            Junction::Random => {
+                // skip the stick from which the ant came
+                let n = self.rng.gen_range(0..sticks.len() - 1);
+                sticks[if n < from { n } else { n + 1 }]
+            }
+            Junction::Next => sticks[(from + 1) % sticks.len()],
+        };
+
+        // the ant is the first or the last one on the next stick
+        let s = self.graph.sticks[next];
+        let mut a = self.ants.remove(i);
+        let speed = a.speed.abs();
+        a.stick = next;
+        let i = if s.from == node {
+            a.position = 0.;
+            a.speed = speed;
+            self.ants.partition_point(|b| b.stick < next)
+        } else {
+            a.position = 1. - f32::EPSILON;
+            a.speed = -speed;
+            self.ants.partition_point(|b| b.stick <= next)
+        };
+        self.ants.insert(i, a);
+    }
+
+    pub fn has_ants(&self) -> bool {
+        !self.ants.is_empty()
+    }
+
+    /// Gets the ants on the graph
+    pub fn ants(&self) -> &[NetAnt] {
+        &self.ants
+    }
+
+    /// Gets the graph on which the ants walk
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Gets the fall of molly if she has already fallen
+    pub fn molly_fall(&self) -> Option<&NetFall> {
+        self.fallen.iter().find(|f| f.typ == AntType::Molly)
+    }
+
+    /// Gets the ants that have already fallen, in the order in which they
+    /// fell
+    pub fn fallen(&self) -> &[NetFall] {
+        &self.fallen
+    }
+
+    /// Gets the total number of collisions so far
+    pub fn collisions(&self) -> usize {
+        self.collisions
+    }
+
+    /// Gets the simulated time
+    pub fn time(&self) -> f32 {
+        self.time as f32 * self.ant_step
+    }
+
+    /// Gets the seed that was used to generate the layout
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl NetworkBuilder {
+    common_setters!();
+
+    /// Sets the index of molly in the generated ants (default is the
+    /// middle one)
+    pub fn molly(mut self, index: usize) -> Self {
+        self.common.molly = Some(index);
+        self
+    }
+
+    /// Sets how the ants choose the stick at junctions (default is random)
+    pub fn junction(mut self, junction: Junction) -> Self {
+        self.junction = junction;
+        self
+    }
+
+    /// Sets what happens when two ants meet (default is that they both turn
+    /// around)
+    pub fn collision(mut self, collision: Collision) -> Self {
+        self.collision = collision;
+        self
+    }
+
+    /// Sets the exact layout of the ants, the count and molly are ignored
+    pub fn ants(mut self, ants: Vec<NetAnt>) -> Self {
+        self.ants = Some(ants);
+        self
+    }
+
+    /// Creates the simulation
+    pub fn build(&self) -> Result<Network> {
+        self.common
+            .validate(self.common.molly.filter(|_| self.ants.is_none()))?;
+
+        let sticks = self.graph.sticks.len();
+        if let Some(a) =
+            self.ants.iter().flatten().find(|a| {
+                a.stick >= sticks || !(0. ..1.).contains(&a.position)
+            })
+        {
+            return Err(Report::msg(format!(
+                "The ant {} is not on the graph",
+                a.id
+            )));
+        }
+
+        let seed = self.common.seed();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut ants = match &self.ants {
+            Some(a) => a.clone(),
+            None => self.place_ants(&mut rng),
+        };
+        ants.sort_by(|a, b| {
+            a.stick
+                .cmp(&b.stick)
+                .then(a.position.total_cmp(&b.position))
+        });
+
+        Ok(Network {
+            graph: self.graph.clone(),
+            ants,
+            ant_step: self.common.step,
+            time: 0,
+            junction: self.junction,
+            collision: self.collision,
+            fallen: vec![],
+            collisions: 0,
+            seed,
+            rng,
+        })
+    }
+
+    /// Places the ants randomly on the sticks
+    fn place_ants(&self, rng: &mut StdRng) -> Vec<NetAnt> {
+        let sticks = self.graph.sticks.len();
+        let mut ants: Vec<_> = (0..self.common.count)
+            .map(|id| {
+                let speed = self.common.speed(rng);
+                NetAnt {
+                    stick: rng.gen_range(0..sticks),
+                    position: rng.gen_range(0.0..1.),
+                    speed: if rng.gen() { speed } else { -speed },
+                    typ: AntType::Some,
+                    id,
+                }
+            })
+            .collect();
+
+        if let Some(a) =
+            ants.get_mut(self.common.molly.unwrap_or(self.common.count / 2))
+        {
+            a.typ = AntType::Molly;
+        }
+        ants
+    }
+}
+
+/// Gets the node at which the ant is, if it walks into it
+fn at_node(graph: &Graph, a: &NetAnt) -> Option<usize> {
+    let s = graph.sticks[a.stick];
+    if a.speed < 0. && a.position <= EPSILON {
+        Some(s.from)
+    } else if a.speed > 0. && a.position >= 1. - EPSILON {
+        Some(s.to)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ants_fall_at_leaves() {
+        let graph: Graph = "0-1,1-2,1-3".parse().unwrap();
+        assert_eq!(graph.leaves().collect::<Vec<_>>(), [0, 2, 3]);
+
+        // the ants meet in the middle of the first stick, then the left one
+        // falls at node 0 and the right one goes through the junction
+        let ant = |position, speed, id| NetAnt {
+            stick: 0,
+            position,
+            speed,
+            typ: AntType::Some,
+            id,
+        };
+        let mut sim = Network::builder(graph)
+            .junction(Junction::Next)
+            .ants(vec![ant(0.25, 1., 0), ant(0.75, -1., 1)])
+            .build()
+            .unwrap();
+        while sim.has_ants() {
+            sim.step();
+        }
+
+        assert_eq!(sim.collisions(), 1);
+        let fallen = sim.fallen();
+        assert_eq!((fallen[0].id, fallen[0].leaf), (0, 0));
+        assert_eq!((fallen[1].id, fallen[1].leaf), (1, 2));
+        assert!((fallen[0].time - 0.75).abs() < 0.01);
+        assert!((fallen[1].time - 1.75).abs() < 0.01);
+    }
+
+    #[test]
+    fn too_large_graphs_are_rejected() {
+        assert!("0-1000000000".parse::<Graph>().is_err());
+        assert!("tree:64".parse::<Graph>().is_err());
+        assert!(Graph::tree(u32::MAX).is_err());
+        assert_eq!(Graph::tree(2).unwrap().sticks().len(), 6);
+    }
+
+    #[test]
+    fn faster_ant_catches_up() {
+        // the ants meet at 0.3667 and both walk back to the node 0
+        let ant = |position, speed, id| NetAnt {
+            stick: 0,
+            position,
+            speed,
+            typ: AntType::Some,
+            id,
+        };
+        let mut sim = Network::builder("0-1".parse().unwrap())
+            .ants(vec![ant(0.1, 2., 0), ant(0.3, 0.5, 1)])
+            .build()
+            .unwrap();
+        while sim.has_ants() {
+            sim.step();
+        }
+
+        assert_eq!(sim.collisions(), 1);
+        let fallen = sim.fallen();
+        assert!(fallen.iter().all(|f| f.leaf == 0));
+        assert!((fallen[0].time - 0.3167).abs() < 0.01);
+        assert!((fallen[1].time - 0.8667).abs() < 0.01);
+    }
+
+    #[test]
+    fn ants_meet_at_junction() {
+        // both ants reach the center at the same time, the first one goes
+        // to the stick of the second one and they collide there
+        let ant = |stick, id| NetAnt {
+            stick,
+            position: 0.1,
+            speed: -1.,
+            typ: AntType::Some,
+            id,
+        };
+        let mut sim = Network::builder("y".parse().unwrap())
+            .junction(Junction::Next)
+            .ants(vec![ant(0, 0), ant(1, 1)])
+            .build()
+            .unwrap();
+        while sim.has_ants() {
+            sim.step();
+        }
+
+        assert_eq!(sim.collisions(), 1);
+        assert_eq!(sim.fallen().len(), 2);
+    }
+}
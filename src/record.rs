@@ -0,0 +1,200 @@
+//! Records of simulation runs. The record contains the parameters, the
+//! initial layout of the ants and all the events (collisions, falls and turns
+//! of molly), so the run can be replayed without the random generator and
+//! checked that everything happens the same way. The records are toml files:
+//!
+//! ```toml
+//! seed = 42
+//! step = 0.001
+//! ring = false
+//! left = "absorbing"  # optional, "absorbing", "reflecting", "periodic" or
+//!                     # { sticky = <time> }
+//! right = "reflecting"
+//! collision = "reverse"
+//! exact = false
+//!
+//! [[ant]]
+//! position = 0.25
+//! speed = 1.0
+//! type = "molly"     # "ant" or "molly"
+//! id = 0
+//!
+//! [[event]]
+//! event = "collision"
+//! left = 0
+//! right = 1
+//! time = 0.25
+//! position = 0.5
+//!
+//! [[event]]
+//! event = "fall"     # "collision", "fall" or "molly_turned"
+//! id = 0
+//! type = "molly"
+//! side = "right"
+//! time = 0.75
+//! ```
+
+use std::fs;
+
+use eyre::{eyre, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use crate::{Ant, AntRod, Boundary, Collision, Event};
+
+/// Record of a simulation run
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Record {
+    /// The seed of the recorded run, it is used only to show the same seed
+    pub seed: u64,
+    pub step: f32,
+    pub ring: bool,
+    #[serde(default)]
+    pub left: Boundary,
+    #[serde(default)]
+    pub right: Boundary,
+    pub collision: Collision,
+    /// The run used the event driven simulation
+    pub exact: bool,
+    /// The initial layout of the ants
+    #[serde(rename = "ant")]
+    pub ants: Vec<Ant>,
+    /// All the events in the order in which they happened
+    #[serde(default, rename = "event")]
+    pub events: Vec<Event>,
+}
+
+impl Record {
+    /// Starts recording simulation that hasn't moved yet
+    pub fn new(sim: &AntRod, exact: bool) -> Self {
+        Self {
+            seed: sim.seed(),
+            step: sim.ant_step(),
+            ring: sim.is_ring(),
+            left: sim.boundaries().0,
+            right: sim.boundaries().1,
+            collision: sim.collision(),
+            exact,
+            ants: sim.ants().to_vec(),
+            events: vec![],
+        }
+    }
+
+    /// Loads the record from file
+    pub fn load(path: &str) -> Result<Self> {
+        let src = fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read record '{path}'"))?;
+        toml::from_str(&src)
+            .wrap_err_with(|| format!("Invalid record '{path}'"))
+    }
+
+    /// Saves the record to file
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, toml::to_string(self)?)
+            .wrap_err_with(|| format!("Failed to write record to '{path}'"))
+    }
+
+    /// Checks that the same events happened in the replayed run as in the
+    /// recorded run
+    pub fn check(&self, events: &[Event]) -> Result<()> {
+        let diff = self
+            .events
+            .iter()
+            .zip(events)
+            .position(|(a, b)| !same_event(a, b));
+        if let Some(i) = diff.or_else(|| {
+            (self.events.len() != events.len())
+                .then(|| self.events.len().min(events.len()))
+        }) {
+            return Err(eyre!(
+                "The replay differs from the record at event #{}: recorded \
+                {:?}, replayed {:?}",
+                i + 1,
+                self.events.get(i),
+                events.get(i),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Compares the events, the times and positions may differ by rounding
+/// errors when the replay samples the event driven simulation differently
+fn same_event(a: &Event, b: &Event) -> bool {
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+    match (a, b) {
+        (
+            Event::Collision {
+                left,
+                right,
+                time,
+                position,
+            },
+            Event::Collision {
+                left: l,
+                right: r,
+                time: t,
+                position: p,
+            },
+        ) => {
+            left == l && right == r && close(*time, *t) && close(*position, *p)
+        }
+        (Event::Fall(a), Event::Fall(b)) => {
+            a.id == b.id
+                && a.typ == b.typ
+                && a.side == b.side
+                && close(a.time, b.time)
+        }
+        (
+            Event::MollyTurned { time, position },
+            Event::MollyTurned {
+                time: t,
+                position: p,
+            },
+        ) => close(*time, *t) && close(*position, *p),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the simulation to the end and gets all its events
+    fn run(mut sim: AntRod) -> Vec<Event> {
+        let mut events = vec![];
+        while sim.has_ants() {
+            sim.step();
+            events.extend_from_slice(sim.events());
+        }
+        events
+    }
+
+    #[test]
+    fn replay_matches_record() {
+        let sim = AntRod::builder().count(20).seed(3).build().unwrap();
+        let mut record = Record::new(&sim, false);
+        record.events = run(sim);
+        assert!(record
+            .events
+            .iter()
+            .any(|e| matches!(e, Event::Collision { .. })));
+
+        let src = toml::to_string(&record).unwrap();
+        let record: Record = toml::from_str(&src).unwrap();
+        assert_eq!(toml::to_string(&record).unwrap(), src);
+
+        let replay = AntRod::builder()
+            .seed(record.seed)
+            .step(record.step)
+            .collision(record.collision)
+            .ants(record.ants.clone())
+            .build()
+            .unwrap();
+        let mut events = run(replay);
+        record.check(&events).unwrap();
+
+        events.pop();
+        assert!(record.check(&events).is_err());
+    }
+}
@@ -0,0 +1,934 @@
+use std::{cmp::Ordering, iter};
+
+use eyre::{Report, Result};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    builder::{common_setters, Common},
+    event::{apply, collide, count_collisions, next_collision, EPSILON},
+    layout::Positions,
+    Ant, AntType, Boundary, Collision, Event, Fall, Side,
+};
+
+/// The simulation of ants on a rod. The ants move by a fixed step, when two
+/// ants meet they collide (by default they both turn around) and when an ant
+/// walks over an end of the rod it falls (unless the end has different
+/// [`Boundary`]).
+#[derive(Clone)]
+pub struct AntRod {
+    // the vector is always ordered by position
+    ants: Vec<Ant>,
+    ant_step: f32,
+    // number of steps with the current `ant_step`
+    time: usize,
+    // time before `ant_step` was last changed
+    time_offset: f32,
+    collision: Collision,
+    // all the ants have the same speed, so the collisions don't have to be
+    // simulated
+    uniform: bool,
+    left: Boundary,
+    right: Boundary,
+    // the ends of the rod are connected so the ants never fall
+    ring: bool,
+    // ants waiting at sticky ends
+    waiting: Vec<Wait>,
+    // ants that have fallen from the rod, in the order in which they fell
+    fallen: Vec<Fall>,
+    // what happened during the last step
+    events: Vec<Event>,
+    // total number of collisions
+    collisions: usize,
+    // the seed used to initialize `rng`, so that the run can be repeated
+    seed: u64,
+    rng: StdRng,
+}
+
+/// Ant waiting at sticky end of the rod
+#[derive(Clone, Debug)]
+struct Wait {
+    id: usize,
+    // when the ant turns around
+    until: f32,
+    // the speed with which the ant leaves
+    speed: f32,
+}
+
+/// Configures and creates [`AntRod`]
+#[derive(Clone, Debug)]
+pub struct AntRodBuilder {
+    common: Common,
+    regular: bool,
+    left: Boundary,
+    right: Boundary,
+    // probability that randomly placed ant faces right
+    bias: f64,
+    positions: Positions,
+    collision: Collision,
+    ants: Option<Vec<Ant>>,
+    // indexes of the tracked ants
+    track: Vec<usize>,
+}
+
+impl AntRod {
+    /// Creates builder for the simulation with the default parameters
+    pub fn builder() -> AntRodBuilder {
+        AntRodBuilder::default()
+    }
+
+    /// Places the ants on the rod, random layouts are generated with the
+    /// rng of the simulation
+    fn place_ants(&mut self, conf: &AntRodBuilder) {
+        if let Some(ants) = &conf.ants {
+            self.ants = ants.clone();
+            self.ants.sort_by(|a, b| {
+                a.position
+                    .partial_cmp(&b.position)
+                    .unwrap_or(Ordering::Equal)
+            });
+            for (i, a) in self.ants.iter_mut().enumerate() {
+                a.id = i;
+            }
+            return;
+        }
+
+        // create vector of ants on the rod
+        let mut ants: Vec<_> = iter::from_fn(|| {
+            Some(Ant::random_facing(&mut self.rng, conf.bias))
+        })
+        .take(conf.common.count)
+        .collect();
+
+        // random positions
+
+        if conf.regular {
+            // regular spacing with ants facing the furtherer side and molly in
+            // center
+            let dis = 1. / (conf.common.count as f32 + 1.);
+
+            // ants on the left
+            for (i, a) in
+                ants.iter_mut().enumerate().take(conf.common.count / 2)
+            {
+                *a = Ant {
+                    position: dis * i as f32 + dis,
+                    speed: 1.,
+                    typ: AntType::Some,
+                    id: 0,
+                };
+            }
+
+            // molly
+            ants[conf.common.count / 2] = Ant {
+                position: 0.5,
+                speed: 1.,
+                typ: AntType::Molly,
+                id: 0,
+            };
+
+            // ants on the right
+            for (i, a) in
+                ants.iter_mut().enumerate().skip(conf.common.count / 2 + 1)
+            {
+                *a = Ant {
+                    position: dis * i as f32 + dis,
+                    speed: -1.,
+                    typ: AntType::Some,
+                    id: 0,
+                };
+            }
+        } else {
+            // the uniform positions are generated together with the ants so
+            // that the layouts for the seeds stay the same
+            if conf.positions != Positions::Uniform {
+                let positions =
+                    conf.positions.sample(&mut self.rng, conf.common.count);
+                for (a, p) in ants.iter_mut().zip(positions) {
+                    a.position = p;
+                }
+            }
+
+            // random positions
+            ants.sort_by(|a, b| {
+                a.position
+                    .partial_cmp(&b.position)
+                    .unwrap_or(Ordering::Equal)
+            });
+            ants[conf.molly_index()].typ = AntType::Molly;
+
+            if let Some((min, max)) = conf.common.speeds {
+                for a in &mut ants {
+                    a.speed = a.speed.signum() * self.rng.gen_range(min..=max);
+                }
+            }
+        }
+
+        // the ids are the initial order of the ants
+        for (i, a) in ants.iter_mut().enumerate() {
+            a.id = i;
+        }
+
+        self.ants = ants;
+    }
+
+    /// Moves all the ants by one step
+    pub fn step(&mut self) {
+        self.events.clear();
+        if self.uniform {
+            self.move_passing();
+        } else {
+            self.move_colliding();
+        }
+
+        self.time += 1;
+        let time = self.time();
+        self.collisions += count_collisions(&self.events);
+
+        // remove those that have fallen
+
+        // remove from the end
+        while let Some(a) = self
+            .ants
+            .last()
+            .filter(|a| self.right == Boundary::Absorbing && a.position >= 1.)
+        {
+            let fall = Fall {
+                id: a.id,
+                typ: a.typ,
+                side: Side::Right,
+                time,
+            };
+            self.fallen.push(fall);
+            self.events.push(Event::Fall(fall));
+            self.ants.pop();
+        }
+
+        // remove from the front
+        let cnt = if self.left == Boundary::Absorbing {
+            self.ants
+                .iter()
+                .position(|a| a.position >= 0.)
+                .unwrap_or(self.ants.len())
+        } else {
+            0
+        };
+        for a in self.ants.drain(0..cnt) {
+            let fall = Fall {
+                id: a.id,
+                typ: a.typ,
+                side: Side::Left,
+                time,
+            };
+            self.fallen.push(fall);
+            self.events.push(Event::Fall(fall));
+        }
+    }
+
+    /// Moves the ants when all of them have the same speed. Two colliding
+    /// ants look the same as if they passed through each other, so the ants
+    /// just move and only their types and ids are kept in order.
+    fn move_passing(&mut self) {
+        self.passing_collisions();
+
+        // update positions, on ring count how many ants went over the end
+        let mut wraps: isize = 0;
+        for a in &mut self.ants {
+            a.position += a.speed * self.ant_step;
+            if !self.ring {
+                continue;
+            }
+            if a.position >= 1. {
+                a.position -= 1.;
+                wraps += 1;
+            } else if a.position < 0. {
+                // adding 1 to tiny negative number may round to 1
+                a.position = (a.position + 1.).min(1. - f32::EPSILON);
+                wraps -= 1;
+            }
+        }
+
+        // sort by position, but retain types and ids
+        let mut typ: Vec<_> =
+            self.ants.iter().map(|a| (a.typ, a.id)).collect();
+        // ants that went over the end moved from one end of the order to the
+        // other, so the types have to rotate with them
+        if !typ.is_empty() {
+            let len = typ.len() as isize;
+            typ.rotate_right(wraps.rem_euclid(len) as usize);
+        }
+        self.ants.sort_by(|a, b| {
+            a.position
+                .partial_cmp(&b.position)
+                .unwrap_or(Ordering::Equal)
+        });
+        for (a, (t, i)) in self.ants.iter_mut().zip(typ.iter()) {
+            a.typ = *t;
+            a.id = *i;
+        }
+    }
+
+    /// Records the collisions during the next step when the ants pass
+    /// through each other. The collisions are where the passing ants (the
+    /// ghosts) cross, the ants that collide are those whose labels are at
+    /// the crossing.
+    fn passing_collisions(&mut self) {
+        let start = self.time();
+        let len = self.ants.len();
+
+        // (time, ghost on the left, ghost on the right)
+        let mut crossings = vec![];
+        for (i, a) in self.ants.iter().enumerate() {
+            if a.speed <= 0. {
+                continue;
+            }
+            // all the ants have the same speed, so only the ghosts closer
+            // than the distance they approach by in one step can cross
+            let reach = 2. * a.speed * self.ant_step;
+            for k in 1..len {
+                if !self.ring && i + k >= len {
+                    break;
+                }
+                let j = (i + k) % len;
+                let b = &self.ants[j];
+                let gap = (b.position - a.position).rem_euclid(1.);
+                if gap >= reach {
+                    break;
+                }
+                if b.speed < 0. {
+                    crossings.push((gap / (a.speed - b.speed), i, j));
+                }
+            }
+        }
+        crossings
+            .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        // the labels stay in their slots, the ghosts swap the slots when
+        // they cross
+        let mut slot: Vec<_> = (0..len).collect();
+        for (dt, i, j) in crossings {
+            let (gi, gj) = (&self.ants[i], &self.ants[j]);
+            let position = (gi.position + gi.speed * dt).rem_euclid(1.);
+            let mut a = Ant {
+                speed: gi.speed,
+                ..self.ants[slot[i]].clone()
+            };
+            let mut b = Ant {
+                speed: gj.speed,
+                ..self.ants[slot[j]].clone()
+            };
+            // the collision is applied only to find whether molly turned
+            apply(
+                self.collision,
+                &mut a,
+                &mut b,
+                start + dt,
+                position,
+                &mut self.events,
+            );
+            slot.swap(i, j);
+        }
+    }
+
+    /// Moves the ants with different speeds (or on rod with walls), each
+    /// collision within the step is resolved at the exact time when it
+    /// happens
+    fn move_colliding(&mut self) {
+        let start = self.time();
+        let mut rest = self.ant_step;
+        loop {
+            let now = start + self.ant_step - rest;
+            self.release(now);
+            self.bounce(now);
+
+            let dt = [
+                next_collision(&self.ants, self.ring),
+                self.next_wall(),
+                self.waiting.iter().map(|w| w.until - now).reduce(f32::min),
+            ]
+            .into_iter()
+            .flatten()
+            .fold(f32::INFINITY, f32::min)
+            .max(0.);
+            if dt >= rest {
+                break;
+            }
+
+            self.move_by(dt);
+            rest -= dt;
+            collide(
+                &mut self.ants,
+                self.collision,
+                self.ring,
+                start + self.ant_step - rest,
+                &mut self.events,
+            );
+        }
+        self.move_by(rest);
+        self.bounce(start + self.ant_step);
+
+        if self.ring {
+            // the ants keep their order around the ring, but the first one
+            // may now be different
+            for a in &mut self.ants {
+                if a.position >= 1. {
+                    a.position -= 1.;
+                } else if a.position < 0. {
+                    a.position = (a.position + 1.).min(1. - f32::EPSILON);
+                }
+            }
+            self.ants.sort_by(|a, b| {
+                a.position
+                    .partial_cmp(&b.position)
+                    .unwrap_or(Ordering::Equal)
+            });
+        }
+    }
+
+    /// Gets the time remaining until an ant reaches reflecting or sticky end
+    fn next_wall(&self) -> Option<f32> {
+        let wall = |b| matches!(b, Boundary::Reflecting | Boundary::Sticky(_));
+        // only the outer ants can reach the ends
+        let left = self
+            .ants
+            .first()
+            .filter(|a| wall(self.left) && a.speed < 0.)
+            .map(|a| a.position / -a.speed);
+        let right = self
+            .ants
+            .last()
+            .filter(|a| wall(self.right) && a.speed > 0.)
+            .map(|a| (1. - a.position) / a.speed);
+        left.into_iter().chain(right).reduce(f32::min)
+    }
+
+    /// Turns around or stops the outer ants that reached reflecting or
+    /// sticky end at time `now`
+    fn bounce(&mut self, now: f32) {
+        let last = self.ants.len().wrapping_sub(1);
+        for (i, side, boundary) in
+            [(0, Side::Left, self.left), (last, Side::Right, self.right)]
+        {
+            let Some(a) = self.ants.get_mut(i) else {
+                continue;
+            };
+            let (at_end, end) = match side {
+                Side::Left if a.speed < 0. => (a.position <= EPSILON, 0.),
+                Side::Right if a.speed > 0. => {
+                    (a.position >= 1. - EPSILON, 1. - f32::EPSILON)
+                }
+                _ => continue,
+            };
+            if !at_end {
+                continue;
+            }
+
+            match boundary {
+                Boundary::Reflecting => {
+                    a.speed = -a.speed;
+                    if a.typ == AntType::Molly {
+                        self.events.push(Event::MollyTurned {
+                            time: now,
+                            position: end,
+                        });
+                    }
+                }
+                Boundary::Sticky(t) => {
+                    self.waiting.push(Wait {
+                        id: a.id,
+                        until: now + t,
+                        speed: -a.speed,
+                    });
+                    a.speed = 0.;
+                }
+                _ => continue,
+            }
+            a.position = end;
+        }
+    }
+
+    /// Lets the ants that waited long enough at sticky end walk back
+    fn release(&mut self, now: f32) {
+        let ants = &mut self.ants;
+        let events = &mut self.events;
+        self.waiting.retain(|w| {
+            if w.until > now {
+                return true;
+            }
+            if let Some(a) = ants.iter_mut().find(|a| a.id == w.id) {
+                a.speed = w.speed;
+                if a.typ == AntType::Molly {
+                    events.push(Event::MollyTurned {
+                        time: now,
+                        position: a.position,
+                    });
+                }
+            }
+            false
+        });
+    }
+
+    fn move_by(&mut self, dt: f32) {
+        for a in &mut self.ants {
+            a.position += a.speed * dt;
+        }
+    }
+
+    pub fn has_ants(&self) -> bool {
+        !self.ants.is_empty()
+    }
+
+    /// Gets the ants on the rod ordered by position
+    pub fn ants(&self) -> &[Ant] {
+        &self.ants
+    }
+
+    /// Gets molly if she is still on the rod
+    pub fn molly(&self) -> Option<&Ant> {
+        self.ants.iter().find(|a| a.typ == AntType::Molly)
+    }
+
+    /// Gets the fall of molly if she has already fallen
+    pub fn molly_fall(&self) -> Option<&Fall> {
+        self.fallen.iter().find(|f| f.typ == AntType::Molly)
+    }
+
+    /// Gets the ants that have already fallen, in the order in which they
+    /// fell
+    pub fn fallen(&self) -> &[Fall] {
+        &self.fallen
+    }
+
+    /// Gets what happened during the last step
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Gets the total number of collisions so far
+    pub fn collisions(&self) -> usize {
+        self.collisions
+    }
+
+    /// Gets the simulated time
+    pub fn time(&self) -> f32 {
+        self.time_offset + self.time as f32 * self.ant_step
+    }
+
+    /// Gets how much the ants move with each step
+    pub fn ant_step(&self) -> f32 {
+        self.ant_step
+    }
+
+    /// Changes how much the ants move with each step
+    pub fn set_ant_step(&mut self, step: f32) {
+        self.time_offset = self.time();
+        self.time = 0;
+        self.ant_step = step;
+    }
+
+    /// Gets the seed that was used to generate the layout
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Checks whether the ends of the rod are connected
+    pub fn is_ring(&self) -> bool {
+        self.ring
+    }
+
+    /// Gets what happens at the left and the right end of the rod
+    pub fn boundaries(&self) -> (Boundary, Boundary) {
+        (self.left, self.right)
+    }
+
+    /// Checks whether the ants can never fall, because there is no
+    /// absorbing end
+    pub fn is_closed(&self) -> bool {
+        self.left != Boundary::Absorbing && self.right != Boundary::Absorbing
+    }
+
+    /// Checks whether any end of the rod is reflecting or sticky
+    pub fn has_walls(&self) -> bool {
+        [self.left, self.right]
+            .iter()
+            .any(|b| matches!(b, Boundary::Reflecting | Boundary::Sticky(_)))
+    }
+
+    /// Checks whether all the ants on the rod have the same speed
+    pub fn same_speeds(&self) -> bool {
+        self.ants
+            .iter()
+            .all(|a| a.speed.abs() == self.ants[0].speed.abs())
+    }
+
+    /// Gets what happens when two ants meet
+    pub fn collision(&self) -> Collision {
+        self.collision
+    }
+}
+
+impl Default for AntRodBuilder {
+    fn default() -> Self {
+        Self {
+            common: Common::default(),
+            regular: false,
+            left: Boundary::default(),
+            right: Boundary::default(),
+            bias: 0.5,
+            positions: Positions::default(),
+            collision: Collision::default(),
+            ants: None,
+            track: vec![],
+        }
+    }
+}
+
+impl AntRodBuilder {
+    common_setters!();
+
+    /// Sets the index of molly in the ants ordered by position (center is
+    /// default)
+    pub fn molly(mut self, index: usize) -> Self {
+        self.common.molly = Some(index);
+        self
+    }
+
+    /// Places the ants regularly facing the further end with molly in the
+    /// center
+    pub fn regular(mut self, regular: bool) -> Self {
+        self.regular = regular;
+        self
+    }
+
+    /// Connects the ends of the rod so that the ants never fall, it is the
+    /// same as periodic boundaries on both ends
+    pub fn ring(mut self, ring: bool) -> Self {
+        if ring {
+            self.left = Boundary::Periodic;
+            self.right = Boundary::Periodic;
+        } else if self.left == Boundary::Periodic {
+            self.left = Boundary::Absorbing;
+            self.right = Boundary::Absorbing;
+        }
+        self
+    }
+
+    /// Sets what happens when an ant reaches the left end (default is that
+    /// it falls)
+    pub fn left(mut self, boundary: Boundary) -> Self {
+        self.left = boundary;
+        self
+    }
+
+    /// Sets what happens when an ant reaches the right end (default is that
+    /// it falls)
+    pub fn right(mut self, boundary: Boundary) -> Self {
+        self.right = boundary;
+        self
+    }
+
+    /// Sets the probability that randomly placed ant faces right (default
+    /// is 0.5)
+    pub fn bias(mut self, right: f64) -> Self {
+        self.bias = right;
+        self
+    }
+
+    /// Sets the distribution of the random positions of the ants (default
+    /// is uniform)
+    pub fn positions(mut self, positions: Positions) -> Self {
+        self.positions = positions;
+        self
+    }
+
+    /// Sets what happens when two ants meet (default is that they both turn
+    /// around). It matters only if the ants have different speeds.
+    pub fn collision(mut self, collision: Collision) -> Self {
+        self.collision = collision;
+        self
+    }
+
+    /// Sets the exact layout of the ants, the count, molly and seed are
+    /// ignored
+    pub fn ants(mut self, ants: Vec<Ant>) -> Self {
+        self.ants = Some(ants);
+        self
+    }
+
+    /// Tracks the ants with the given indexes in the ants ordered by
+    /// position, the ants get types [`AntType::Tracked`] numbered in the
+    /// given order
+    pub fn track(mut self, indexes: Vec<usize>) -> Self {
+        self.track = indexes;
+        self
+    }
+
+    /// Creates the simulation
+    pub fn build(&self) -> Result<AntRod> {
+        let molly = self.ants.is_none().then(|| self.molly_index());
+        self.common.validate(molly)?;
+
+        if !(0. ..=1.).contains(&self.bias) {
+            return Err(Report::msg(format!(
+                "Invalid bias {}, it must be in the range [0, 1]",
+                self.bias
+            )));
+        }
+
+        self.positions.validate()?;
+
+        let ring = self.left == Boundary::Periodic;
+        if ring != (self.right == Boundary::Periodic) {
+            return Err(Report::msg(
+                "The periodic boundary must be on both ends of the rod",
+            ));
+        }
+
+        let seed = self.common.seed();
+        let mut res = AntRod {
+            ants: vec![],
+            ant_step: self.common.step,
+            time: 0,
+            time_offset: 0.,
+            collision: self.collision,
+            uniform: true,
+            left: self.left,
+            right: self.right,
+            ring,
+            waiting: vec![],
+            fallen: vec![],
+            events: vec![],
+            collisions: 0,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        };
+        res.place_ants(self);
+
+        // continue the numbering of the ants that are already tracked
+        let first = res
+            .ants
+            .iter()
+            .filter_map(|a| match a.typ {
+                AntType::Tracked(n) => Some(n + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or_default();
+        for (n, &i) in self.track.iter().enumerate() {
+            match res.ants.get(i).map(|a| a.typ) {
+                None => {
+                    return Err(Report::msg(format!(
+                        "Invalid tracked index {i} out of {}",
+                        res.ants.len()
+                    )))
+                }
+                Some(AntType::Some) => {}
+                Some(AntType::Molly) => {
+                    return Err(Report::msg(format!(
+                        "Ant {i} is molly, it cannot be tracked"
+                    )))
+                }
+                Some(_) => {
+                    return Err(Report::msg(format!(
+                        "Ant {i} is tracked more than once"
+                    )))
+                }
+            }
+            res.ants[i].typ = AntType::Tracked(first + n);
+        }
+
+        // the ants can pass through each other only if there are no walls
+        let walls = res.has_walls();
+        let same_speeds = res.same_speeds();
+
+        // the faster ants may trap the slower ones at the wall forever
+        if walls && !same_speeds && self.collision == Collision::Reverse {
+            return Err(Report::msg(
+                "The ants with different speeds may never leave rod with \
+                reflecting or sticky end when they reverse, use the exchange \
+                collision",
+            ));
+        }
+
+        res.uniform = !walls && same_speeds;
+        Ok(res)
+    }
+
+    fn molly_index(&self) -> usize {
+        self.common.molly.unwrap_or(self.common.count / 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        event::EventRod, Ant, AntType, Boundary, Collision, Event, Side,
+    };
+
+    use super::AntRod;
+
+    #[test]
+    fn same_seed_same_layout() {
+        let conf = AntRod::builder().count(50).seed(42);
+        let a = conf.build().unwrap();
+        let b = conf.build().unwrap();
+        assert_eq!(a.ants, b.ants);
+
+        let c = conf.seed(43).build().unwrap();
+        assert_ne!(a.ants, c.ants);
+    }
+
+    #[test]
+    fn colliding_matches_event_driven() {
+        // with `Collision::Reverse` the ants may bounce for a long time and
+        // the rounding errors grow, so only exchange is compared
+        for seed in 0..20 {
+            let mut sim = AntRod::builder()
+                .count(15)
+                .speeds(0.5, 2.)
+                .collision(Collision::Exchange)
+                .seed(seed)
+                .build()
+                .unwrap();
+
+            let mut exact =
+                EventRod::new(sim.ants.clone()).collision(Collision::Exchange);
+            while exact.has_ants() {
+                exact.advance_to(exact.time() + 0.1);
+            }
+
+            while sim.has_ants() {
+                sim.step();
+            }
+
+            for f in sim.fallen() {
+                let e = exact.fallen().iter().find(|e| e.id == f.id).unwrap();
+                assert_eq!(f.side, e.side);
+                assert!((f.time - e.time).abs() <= 2. * 0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn passing_collisions_match_event_driven() {
+        // pairs of ids that collided
+        fn collisions(events: &[Event]) -> Vec<(usize, usize)> {
+            events
+                .iter()
+                .filter_map(|e| match e {
+                    Event::Collision { left, right, .. } => {
+                        Some((*left, *right))
+                    }
+                    _ => None,
+                })
+                .collect()
+        }
+
+        for seed in 0..20 {
+            let mut sim =
+                AntRod::builder().count(20).seed(seed).build().unwrap();
+            let mut exact = EventRod::new(sim.ants.clone());
+
+            let mut expected = vec![];
+            while let Some(dt) = exact.next_event() {
+                exact.advance_to(exact.time() + dt);
+                expected.extend(collisions(exact.events()));
+            }
+
+            let mut actual = vec![];
+            while sim.has_ants() {
+                sim.step();
+                actual.extend(collisions(sim.events()));
+            }
+
+            expected.sort();
+            actual.sort();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn tracked_ants_keep_identity() {
+        let mut sim = AntRod::builder()
+            .count(20)
+            .seed(1)
+            .track(vec![2, 15])
+            .build()
+            .unwrap();
+        while sim.has_ants() {
+            sim.step();
+        }
+
+        for (n, id) in [2, 15].into_iter().enumerate() {
+            let f = sim.fallen().iter().find(|f| f.id == id).unwrap();
+            assert_eq!(f.typ, AntType::Tracked(n));
+        }
+    }
+
+    #[test]
+    fn tracking_continues_numbering() {
+        // the ant from scenario is already tracked
+        let ant = |position, typ| Ant {
+            position,
+            speed: 1.,
+            typ,
+            id: 0,
+        };
+        let sim = AntRod::builder()
+            .ants(vec![
+                ant(0.2, AntType::Tracked(0)),
+                ant(0.4, AntType::Molly),
+                ant(0.6, AntType::Some),
+            ])
+            .track(vec![2])
+            .build()
+            .unwrap();
+        let types: Vec<_> = sim.ants().iter().map(|a| a.typ).collect();
+        assert_eq!(
+            types,
+            [AntType::Tracked(0), AntType::Molly, AntType::Tracked(1)]
+        );
+        assert!(AntRod::builder()
+            .ants(vec![ant(0.2, AntType::Tracked(0))])
+            .track(vec![0])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn walls_send_all_ants_to_other_end() {
+        for left in [Boundary::Reflecting, Boundary::Sticky(0.1)] {
+            let mut sim = AntRod::builder()
+                .count(20)
+                .seed(8)
+                .left(left)
+                .build()
+                .unwrap();
+            while sim.has_ants() {
+                sim.step();
+            }
+            assert_eq!(sim.fallen().len(), 20);
+            assert!(sim.fallen().iter().all(|f| f.side == Side::Right));
+
+            // with different speeds the ants may get trapped between the
+            // wall and each other, so they must exchange their speeds
+            let conf = AntRod::builder().seed(8).speeds(0.5, 1.5).left(left);
+            assert!(conf.build().is_err());
+        }
+
+        for left in [Boundary::Reflecting, Boundary::Sticky(0.1)] {
+            let mut sim = AntRod::builder()
+                .count(20)
+                .seed(8)
+                .speeds(0.5, 1.5)
+                .collision(Collision::Exchange)
+                .left(left)
+                .build()
+                .unwrap();
+            while sim.has_ants() {
+                sim.step();
+            }
+            assert_eq!(sim.fallen().len(), 20);
+            assert!(sim.fallen().iter().all(|f| f.side == Side::Right));
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Scenario files describe exact initial layout of the ants. They are toml
+//! files with one `[[ant]]` table for each ant:
+//!
+//! ```toml
+//! [[ant]]
+//! position = 0.25     # in the range [0, 1)
+//! direction = "right" # "left" or "right"
+//! speed = 1.0         # optional, default is 1
+//! type = "molly"      # optional, "ant", "molly" or "tracked", default is
+//!                     # "ant"
+//! ```
+
+use std::{cmp::Ordering, fs};
+
+use eyre::{eyre, Result, WrapErr};
+use serde::Deserialize;
+
+use crate::{Ant, AntType};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Scenario {
+    // the entries are parsed one by one so that the errors say which ant
+    // is wrong
+    #[serde(default, rename = "ant")]
+    ants: Vec<toml::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AntEntry {
+    position: f32,
+    direction: Direction,
+    #[serde(default = "default_speed")]
+    speed: f32,
+    #[serde(default, rename = "type")]
+    typ: EntryType,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Direction {
+    Left,
+    Right,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+enum EntryType {
+    #[default]
+    Ant,
+    Molly,
+    Tracked,
+}
+
+fn default_speed() -> f32 {
+    1.
+}
+
+/// Loads the ants from the scenario file, the ants are ordered by position
+pub fn load(path: &str) -> Result<Vec<Ant>> {
+    let src = fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read scenario '{path}'"))?;
+    parse(&src).wrap_err_with(|| format!("Invalid scenario '{path}'"))
+}
+
+/// Parses scenario from its toml source, the ants are ordered by position
+pub fn parse(src: &str) -> Result<Vec<Ant>> {
+    let scenario: Scenario = toml::from_str(src)?;
+
+    let mut molly = None;
+    let mut tracked = 0;
+    let mut ants = Vec::with_capacity(scenario.ants.len());
+    for (i, e) in scenario.ants.into_iter().enumerate() {
+        // the entries are numbered from 1 as they appear in the file
+        let n = i + 1;
+        let e: AntEntry = e.try_into().map_err(|e| eyre!("ant #{n}: {e}"))?;
+        if !(0. ..1.).contains(&e.position) {
+            return Err(eyre!(
+                "ant #{n}: position {} is not in the range [0, 1)",
+                e.position
+            ));
+        }
+        if !e.speed.is_finite() || e.speed <= 0. {
+            return Err(eyre!(
+                "ant #{n}: speed {} must be positive number",
+                e.speed
+            ));
+        }
+
+        let typ = match e.typ {
+            EntryType::Ant => AntType::Some,
+            EntryType::Molly => {
+                if let Some(m) = molly {
+                    return Err(eyre!(
+                        "ant #{n}: there can be only one molly, ant #{m} is \
+                        already molly"
+                    ));
+                }
+                molly = Some(n);
+                AntType::Molly
+            }
+            EntryType::Tracked => {
+                tracked += 1;
+                AntType::Tracked(tracked - 1)
+            }
+        };
+
+        ants.push(Ant {
+            position: e.position,
+            speed: match e.direction {
+                Direction::Left => -e.speed,
+                Direction::Right => e.speed,
+            },
+            typ,
+            id: 0,
+        });
+    }
+
+    ants.sort_by(|a, b| {
+        a.position
+            .partial_cmp(&b.position)
+            .unwrap_or(Ordering::Equal)
+    });
+
+    Ok(ants)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the ants and checks that it fails on the given ant
+    fn fails_on(src: &str, n: usize) {
+        let err = parse(src).unwrap_err().to_string();
+        assert!(err.starts_with(&format!("ant #{n}:")), "{err}");
+    }
+
+    #[test]
+    fn invalid_ants_are_reported() {
+        let valid = "[[ant]]\nposition = 0.5\ndirection = \"left\"\n";
+        assert_eq!(parse(valid).unwrap().len(), 1);
+
+        let position = "[[ant]]\nposition = 1.0\ndirection = \"left\"\n";
+        fails_on(&(valid.to_owned() + position), 2);
+
+        let speed = "speed = 0.0\n";
+        fails_on(&(valid.to_owned() + speed), 1);
+        fails_on(&(valid.to_owned() + "speed = -1.0\n"), 1);
+
+        let molly = valid.to_owned() + "type = \"molly\"\n";
+        fails_on(&format!("{valid}{molly}{molly}"), 3);
+
+        fails_on(&(valid.to_owned() + "color = \"red\"\n"), 1);
+    }
+}
@@ -0,0 +1,148 @@
+use std::cmp::Ordering;
+
+use crate::{Ant, AntType, Fall, Side};
+
+/// Computes when and on which side Molly falls from the rod without
+/// simulating it. Expects that `ants` is ordered by position and that all the
+/// ants have the same speed. Returns [`None`] if there is no Molly.
+///
+/// When two ants collide and turn around, it looks the same as if they just
+/// passed through each other, so the set of fall times is known right away:
+/// ant walking left falls after walking its position, ant walking right after
+/// walking the rest of the rod. The ants never change their order, so if
+/// there are `l` ants walking left, the first `l` ants fall from the left in
+/// order and the rest falls from the right in reverse order.
+pub fn solve(ants: &[Ant]) -> Option<Fall> {
+    let index = ants.iter().position(|a| a.typ == AntType::Molly)?;
+    let molly = &ants[index];
+
+    let left = ants.iter().filter(|a| a.speed < 0.).count();
+
+    let (side, rank, mut times): (_, _, Vec<_>) = if index < left {
+        (
+            Side::Left,
+            index,
+            ants.iter()
+                .filter(|a| a.speed < 0.)
+                .map(|a| a.position / -a.speed)
+                .collect(),
+        )
+    } else {
+        (
+            Side::Right,
+            ants.len() - index - 1,
+            ants.iter()
+                .filter(|a| a.speed >= 0.)
+                .map(|a| (1. - a.position) / a.speed)
+                .collect(),
+        )
+    };
+
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    Some(Fall {
+        id: molly.id,
+        typ: molly.typ,
+        side,
+        time: times[rank],
+    })
+}
+
+/// Computes when all the ants on a ring (and so also molly) return to their
+/// initial positions and directions. Expects that `ants` is ordered by
+/// position and that all the ants have the same speed. Returns [`None`] if
+/// there is no molly.
+///
+/// After the time it takes to walk around the ring once, all the passing
+/// through ants are back in their initial positions, but the ants have
+/// rotated by the number of ants walking right minus the number of ants
+/// walking left. Molly is back at her start once the rotations add up to a
+/// multiple of the number of ants.
+pub fn ring_period(ants: &[Ant]) -> Option<f32> {
+    let molly = ants.iter().find(|a| a.typ == AntType::Molly)?;
+
+    let len = ants.len() as isize;
+    let right = ants.iter().filter(|a| a.speed >= 0.).count() as isize;
+    let shift = (right - (len - right)).rem_euclid(len) as usize;
+
+    let rounds = ants.len() / gcd(ants.len(), shift);
+    Some(rounds as f32 / molly.speed.abs())
+}
+
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{event::EventRod, AntRod, AntRodBuilder, AntType, Side};
+
+    use super::{ring_period, solve};
+
+    /// Runs the stepped simulation until molly falls
+    fn step_molly(sim: &mut AntRod) -> (Side, f32) {
+        loop {
+            let molly = sim.molly().map(|a| a.speed).unwrap();
+            sim.step();
+            if sim.molly().is_none() {
+                let side = if molly < 0. { Side::Left } else { Side::Right };
+                return (side, sim.time());
+            }
+        }
+    }
+
+    fn check(conf: AntRodBuilder) {
+        let mut sim = conf.build().unwrap();
+
+        let fall = solve(sim.ants()).unwrap();
+
+        let mut exact = EventRod::new(sim.ants().to_vec());
+        while exact.has_ants() {
+            exact.advance_to(exact.time() + 0.1);
+        }
+        let exact = exact
+            .fallen()
+            .iter()
+            .find(|f| f.typ == AntType::Molly)
+            .unwrap();
+        assert_eq!(fall.side, exact.side);
+        assert!((fall.time - exact.time).abs() < 1e-4);
+
+        let (side, time) = step_molly(&mut sim);
+        assert_eq!(fall.side, side);
+        assert!((fall.time - time).abs() <= 2. * 0.001);
+    }
+
+    #[test]
+    fn matches_simulation() {
+        for seed in 0..100 {
+            check(AntRod::builder().count(25).seed(seed));
+            check(AntRod::builder().count(10).molly(0).seed(seed));
+            check(AntRod::builder().count(10).molly(9).seed(seed));
+        }
+        check(AntRod::builder().count(25).regular(true));
+    }
+
+    #[test]
+    fn ring_period_matches_simulation() {
+        for seed in 0..20 {
+            let conf = AntRod::builder().ring(true).count(6).seed(seed);
+            let mut sim = conf.build().unwrap();
+
+            let start = sim.molly().cloned().unwrap();
+
+            let period = ring_period(sim.ants()).unwrap();
+            let steps = (period / 0.001).round() as usize;
+            for _ in 0..steps {
+                sim.step();
+            }
+
+            let end = sim.molly().cloned().unwrap();
+            assert!((start.position - end.position).abs() < 1e-3);
+            assert_eq!(start.speed, end.speed);
+        }
+    }
+}
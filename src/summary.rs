@@ -0,0 +1,126 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{Ant, AntType, Fall, Side};
+
+/// Summary of a finished run
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Summary {
+    pub molly: Option<Fall>,
+    pub collisions: usize,
+    /// Number of ants that fell from the left end
+    pub left: usize,
+    /// Number of ants that fell from the right end
+    pub right: usize,
+    /// When the last ant fell, [`None`] if no ant fell
+    pub last: Option<f32>,
+}
+
+/// The ants on the rod at one moment of the run
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Snapshot<'a> {
+    pub time: f32,
+    pub ants: &'a [Ant],
+}
+
+/// The results of a finished run with all its falls
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Results<'a> {
+    pub seed: u64,
+    #[serde(flatten)]
+    pub summary: Summary,
+    pub falls: &'a [Fall],
+}
+
+impl Summary {
+    /// Summarizes run with the given falls (in the order in which they
+    /// happened) and number of collisions
+    pub fn new(fallen: &[Fall], collisions: usize) -> Self {
+        let left = fallen.iter().filter(|f| f.side == Side::Left).count();
+        Self {
+            molly: fallen.iter().find(|f| f.typ == AntType::Molly).copied(),
+            collisions,
+            left,
+            right: fallen.len() - left,
+            last: fallen.last().map(|f| f.time),
+        }
+    }
+}
+
+impl Snapshot<'_> {
+    /// The header of the csv table with the snapshots
+    pub const CSV_HEADER: &'static str = "time,id,type,position,speed";
+
+    /// Gets the csv lines with the ants, one line for each ant
+    pub fn to_csv(&self) -> String {
+        let mut res = String::new();
+        for a in self.ants {
+            // writing to string never fails
+            _ = writeln!(
+                res,
+                "{},{},{},{},{}",
+                self.time,
+                a.id,
+                type_name(a.typ),
+                a.position,
+                a.speed
+            );
+        }
+        res
+    }
+}
+
+impl Results<'_> {
+    /// Gets the csv table with the falls
+    pub fn to_csv(&self) -> String {
+        let mut res = "id,type,side,time\n".to_owned();
+        for f in self.falls {
+            _ = writeln!(
+                res,
+                "{},{},{},{}",
+                f.id,
+                type_name(f.typ),
+                f.side,
+                f.time
+            );
+        }
+        res
+    }
+}
+
+/// Gets the name of the type of ant as it is serialized, without the number
+/// of tracked ant
+fn type_name(typ: AntType) -> String {
+    match serde_json::to_value(typ) {
+        Ok(Value::String(name)) => name,
+        Ok(Value::Object(o)) => o.keys().next().cloned().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_uses_serde_names() {
+        let fall = |id, typ| Fall {
+            id,
+            typ,
+            side: Side::Left,
+            time: 0.5,
+        };
+        let falls = [fall(0, AntType::Some), fall(1, AntType::Tracked(3))];
+        let res = Results {
+            seed: 1,
+            summary: Summary::new(&falls, 0),
+            falls: &falls,
+        };
+        assert_eq!(
+            res.to_csv(),
+            "id,type,side,time\n0,ant,left,0.5\n1,tracked,left,0.5\n"
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use std::fmt::Write;
+
+use crate::{Ant, AntType, Event, Side};
+
+// size of the plot area in the svg
+const WIDTH: f32 = 600.;
+const HEIGHT: f32 = 600.;
+// space around the plot area for the axis labels
+const MARGIN: f32 = 60.;
+
+/// Records the trajectories of the ants so that they can be exported as
+/// space-time diagram in svg
+pub struct Recorder {
+    // indexed by the ant id
+    paths: Vec<Path>,
+    // (time, position)
+    collisions: Vec<(f32, f32)>,
+    fall_points: Vec<(f32, f32, AntType)>,
+    time: f32,
+}
+
+struct Path {
+    typ: AntType,
+    // the trajectory is split into segments when the ant wraps around the
+    // ring, the points are (time, position)
+    segments: Vec<Vec<(f32, f32)>>,
+    // the last known position and speed
+    position: f32,
+    speed: f32,
+    fallen: bool,
+}
+
+impl Path {
+    /// Adds point to the trajectory, splits it if the ant went over the end
+    /// of the ring since the last point
+    fn point(&mut self, time: f32, position: f32) {
+        let Some(seg) = self.segments.last_mut() else {
+            return;
+        };
+        if (position - self.position).abs() > 0.5 {
+            let (from, to) = if position < self.position {
+                (1., 0.)
+            } else {
+                (0., 1.)
+            };
+            seg.push((time, from));
+            self.segments.push(vec![(time, to), (time, position)]);
+        } else {
+            seg.push((time, position));
+        }
+        self.position = position;
+    }
+}
+
+impl Recorder {
+    /// Starts recording, expects that the ids of the ants are their indexes
+    pub fn new(ants: &[Ant]) -> Self {
+        let paths = ants
+            .iter()
+            .map(|a| Path {
+                typ: a.typ,
+                segments: vec![vec![(0., a.position)]],
+                position: a.position,
+                speed: a.speed,
+                fallen: false,
+            })
+            .collect();
+
+        Self {
+            paths,
+            collisions: vec![],
+            fall_points: vec![],
+            time: 0.,
+        }
+    }
+
+    /// Records the state of the simulation, expects that `events` are all
+    /// the events since the last recorded state
+    pub fn record(&mut self, ants: &[Ant], events: &[Event], time: f32) {
+        // the ants that turned in a collision, they have exact turn points
+        let mut collided = vec![false; self.paths.len()];
+
+        for e in events {
+            match e {
+                Event::Collision {
+                    left,
+                    right,
+                    time,
+                    position,
+                } => {
+                    for id in [*left, *right] {
+                        self.paths[id].point(*time, *position);
+                        collided[id] = true;
+                    }
+                    self.collisions.push((*time, *position));
+                }
+                Event::Fall(f) => {
+                    let pos = match f.side {
+                        Side::Left => 0.,
+                        Side::Right => 1.,
+                    };
+                    let path = &mut self.paths[f.id];
+                    path.fallen = true;
+                    if let Some(seg) = path.segments.last_mut() {
+                        seg.push((f.time, pos));
+                    }
+                    self.fall_points.push((f.time, pos, f.typ));
+                }
+                Event::MollyTurned { .. } => {}
+            }
+        }
+
+        for a in ants {
+            let path = &mut self.paths[a.id];
+            let wrapped = (a.position - path.position).abs() > 0.5;
+            if wrapped || (a.speed != path.speed && !collided[a.id]) {
+                // the ant went over the end of the ring or it bounced from
+                // wall, there is no event for it
+                path.point(time, a.position);
+            }
+            path.position = a.position;
+            path.speed = a.speed;
+        }
+
+        self.time = time;
+    }
+
+    /// Forgets everything recorded after `time`, so that the recording can
+    /// continue from an earlier state of the simulation given by `ants`
+    pub fn rewind(&mut self, ants: &[Ant], time: f32) {
+        for path in &mut self.paths {
+            path.segments
+                .retain(|s| s.first().is_some_and(|p| p.0 <= time));
+            for seg in &mut path.segments {
+                seg.retain(|p| p.0 <= time);
+            }
+        }
+        for a in ants {
+            let path = &mut self.paths[a.id];
+            path.fallen = false;
+            path.position = a.position;
+            path.speed = a.speed;
+            match path.segments.last_mut() {
+                Some(seg) => seg.push((time, a.position)),
+                None => path.segments.push(vec![(time, a.position)]),
+            }
+        }
+
+        self.collisions.retain(|c| c.0 <= time);
+        self.fall_points.retain(|f| f.0 <= time);
+        self.time = time;
+    }
+
+    /// Creates the svg with the recorded trajectories, ants that are still
+    /// on the rod end at the last recorded time
+    pub fn to_svg(&self) -> String {
+        let end = self.time.max(f32::EPSILON);
+        let x = |pos: f32| MARGIN + pos * WIDTH;
+        let y = |time: f32| MARGIN + time / end * HEIGHT;
+
+        let mut res = String::new();
+        // writing to string never fails
+        _ = writeln!(
+            res,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">
+<rect width="100%" height="100%" fill="white"/>"#,
+            w = WIDTH + 2. * MARGIN,
+            h = HEIGHT + 2. * MARGIN,
+        );
+
+        // the rod ends
+        for pos in [0., 1.] {
+            _ = writeln!(
+                res,
+                r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="gray"/>"#,
+                y(0.),
+                y(end),
+                x = x(pos),
+            );
+        }
+
+        // position axis
+        for i in 0..=4 {
+            let pos = i as f32 / 4.;
+            _ = writeln!(
+                res,
+                r#"<text x="{}" y="{}" text-anchor="middle">{pos}</text>"#,
+                x(pos),
+                MARGIN - 8.,
+            );
+        }
+        _ = writeln!(
+            res,
+            r#"<text x="{}" y="{}" text-anchor="middle">position</text>"#,
+            x(0.5),
+            MARGIN - 30.,
+        );
+
+        // time axis, with the same scale as the drawer
+        let tick = tick_size(end * 100.);
+        let mut t = 0.;
+        while t <= end * 100. {
+            _ = writeln!(
+                res,
+                r#"<line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="lightgray"/>
+<text x="{}" y="{y}" text-anchor="end" dominant-baseline="middle">{t:.1}s</text>"#,
+                x(0.),
+                x(1.),
+                x(0.) - 6.,
+                y = y(t / 100.),
+            );
+            t += tick;
+        }
+        _ = writeln!(
+            res,
+            r#"<text x="{x}" y="{y}" text-anchor="middle" transform="rotate(-90 {x} {y})">time</text>"#,
+            x = MARGIN - 45.,
+            y = y(end / 2.),
+        );
+
+        // the trajectories, tracked ants and molly last so that they are on
+        // top
+        let mut paths: Vec<_> = self.paths.iter().collect();
+        paths.sort_by_key(|p| match p.typ {
+            AntType::Molly => 2,
+            AntType::Tracked(_) => 1,
+            _ => 0,
+        });
+        for p in paths {
+            let (color, width) = match p.typ {
+                AntType::Molly => ("#c020c0", 3),
+                AntType::Tracked(n) => (tracked_color(n), 2),
+                _ => ("black", 1),
+            };
+            for (i, seg) in p.segments.iter().enumerate() {
+                let mut points = String::new();
+                for (t, pos) in seg {
+                    _ = write!(points, "{},{} ", x(*pos), y(*t));
+                }
+                // ant that is still on the rod continues to the end
+                if i + 1 == p.segments.len() && !p.fallen {
+                    _ = write!(points, "{},{}", x(p.position), y(end));
+                }
+                _ = writeln!(
+                    res,
+                    r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="{width}"/>"#,
+                    points.trim_end(),
+                );
+            }
+        }
+
+        for (t, pos) in &self.collisions {
+            _ = writeln!(
+                res,
+                r#"<circle cx="{}" cy="{}" r="3" fill="red"/>"#,
+                x(*pos),
+                y(*t),
+            );
+        }
+
+        for (t, pos, typ) in &self.fall_points {
+            let color = match typ {
+                AntType::Molly => "#c020c0",
+                AntType::Tracked(n) => tracked_color(*n),
+                _ => "black",
+            };
+            _ = writeln!(
+                res,
+                r#"<circle cx="{}" cy="{}" r="5" fill="none" stroke="{color}" stroke-width="2"/>"#,
+                x(*pos),
+                y(*t),
+            );
+        }
+
+        res += "</svg>\n";
+        res
+    }
+}
+
+/// Chooses distance of the ticks on axis of the given length so that there
+/// are about 10 of them
+fn tick_size(len: f32) -> f32 {
+    let raw = (len / 10.).max(f32::EPSILON);
+    let mag = 10f32.powf(raw.log10().floor());
+    [1., 2., 5., 10.]
+        .into_iter()
+        .map(|m| m * mag)
+        .find(|t| *t >= raw)
+        .unwrap_or(10. * mag)
+}
+
+/// Gets the color of tracked ant with the given number
+fn tracked_color(n: usize) -> &'static str {
+    // red, blue, green, cyan, yellow
+    const COLORS: [&str; 5] =
+        ["#d03030", "#3050d0", "#30a030", "#20a0a0", "#c09000"];
+    COLORS[n % COLORS.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AntRod, Collision};
+
+    use super::*;
+
+    #[test]
+    fn rewind_drops_later_falls() {
+        let start = AntRod::builder().count(10).seed(3).build().unwrap();
+        let mut recorder = Recorder::new(start.ants());
+        let mut sim = start.clone();
+        while sim.has_ants() {
+            sim.step();
+            recorder.record(sim.ants(), sim.events(), sim.time());
+        }
+        assert_eq!(recorder.fall_points.len(), 10);
+
+        // continue from the start with different step
+        recorder.rewind(start.ants(), start.time());
+        assert!(recorder.fall_points.is_empty());
+        let mut sim = start;
+        sim.set_ant_step(0.002);
+        while sim.has_ants() {
+            sim.step();
+            recorder.record(sim.ants(), sim.events(), sim.time());
+        }
+        assert_eq!(recorder.fall_points.len(), 10);
+    }
+
+    #[test]
+    fn records_exchanged_speeds() {
+        // the fast ant catches up the slow one at 0.6, they keep their
+        // directions and only exchange their speeds
+        let ant = |position, speed, id| Ant {
+            position,
+            speed,
+            typ: AntType::Some,
+            id,
+        };
+        let mut sim = AntRod::builder()
+            .ants(vec![ant(0.2, 1., 0), ant(0.4, 0.5, 1)])
+            .collision(Collision::Exchange)
+            .build()
+            .unwrap();
+        // record only every 70 steps, the collision is between two records
+        let mut recorder = Recorder::new(sim.ants());
+        let mut events = vec![];
+        while sim.has_ants() {
+            for _ in 0..70 {
+                sim.step();
+                events.extend_from_slice(sim.events());
+            }
+            recorder.record(sim.ants(), &events, sim.time());
+            events.clear();
+        }
+
+        let [(time, pos)] = recorder.collisions[..] else {
+            panic!("expected one collision");
+        };
+        assert!((time - 0.4).abs() < 0.01 && (pos - 0.6).abs() < 0.01);
+        for p in &recorder.paths {
+            // start, the exact collision and the fall
+            let [_, turn, _] = p.segments[0][..] else {
+                panic!("expected three points");
+            };
+            assert_eq!(turn, (time, pos));
+        }
+    }
+}